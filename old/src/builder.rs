@@ -0,0 +1,290 @@
+use crate::{Term, ArenaTerm, ParseError, Number, Name};
+use bumpalo::Bump;
+use std::fmt;
+use std::ops::{Add, Sub, Mul, Div, Neg};
+
+/// hands out `Expr`s allocated in `arena`, so terms can be written like `3.0 * ctx.sin(x.pow(2)) + x`.
+#[derive(Clone, Copy)]
+pub struct Context<'arena> {
+    arena: &'arena Bump,
+}
+
+/// a copyable reference to a term living in a `Context`'s arena.
+#[derive(Clone, Copy)]
+pub struct Expr<'arena> {
+    term: &'arena Term<'arena>,
+    ctx: Context<'arena>,
+}
+
+/// anything that can stand on either side of an operator with an `Expr`: other `Expr`s and numbers.
+pub trait IntoExpr<'arena> {
+    fn into_expr(self, ctx: Context<'arena>) -> Expr<'arena>;
+}
+
+impl<'arena> Context<'arena> {
+    pub fn new(arena: &'arena Bump) -> Self {
+        Context { arena }
+    }
+
+    pub fn arena(self) -> &'arena Bump {
+        self.arena
+    }
+
+    pub fn term(self, term: Term<'arena>) -> Expr<'arena> {
+        Expr { term: self.arena.alloc(term), ctx: self }
+    }
+
+    /// exact for integers and `Number::Rational`s, inexact for `f64`s.
+    pub fn constant(self, c: impl Into<Number>) -> Expr<'arena> {
+        self.term(Term::Constant(c.into()))
+    }
+
+    /// the single-letter variable `var`, or an error if it isn't an ascii letter or it's `e` or `i`.
+    pub fn var(self, var: char) -> Result<Expr<'arena>, ParseError> {
+        Ok(self.term(Term::var(var)?))
+    }
+
+    /// a variable like `theta` or `i_L`, if `name` follows the naming rules.
+    pub fn named(self, name: &str) -> Result<Expr<'arena>, ParseError> {
+        Ok(self.term(Term::Var(Name::new(name)?)))
+    }
+
+    /// a named parameter like `k` or `ω₀`, which stays symbolic.
+    pub fn symbol(self, name: &str) -> Expr<'arena> {
+        self.term(Term::Symbol(self.arena.alloc_str(name)))
+    }
+
+    pub fn pi(self) -> Expr<'arena> {
+        self.term(Term::Pi)
+    }
+
+    pub fn e(self) -> Expr<'arena> {
+        self.term(Term::E)
+    }
+
+    /// the imaginary unit.
+    pub fn i(self) -> Expr<'arena> {
+        self.term(Term::I)
+    }
+
+    pub fn parse(self, input: &str) -> Result<Expr<'arena>, ParseError> {
+        Ok(self.term(*Term::parse_in(input, self.arena)?))
+    }
+
+    pub fn sin(self, e: impl IntoExpr<'arena>) -> Expr<'arena> {
+        self.term(Term::Sin(e.into_expr(self).boxed()))
+    }
+
+    pub fn cos(self, e: impl IntoExpr<'arena>) -> Expr<'arena> {
+        self.term(Term::Cos(e.into_expr(self).boxed()))
+    }
+
+    pub fn ln(self, e: impl IntoExpr<'arena>) -> Expr<'arena> {
+        self.term(Term::Ln(e.into_expr(self).boxed()))
+    }
+
+    /// `e^x`
+    pub fn exp(self, e: impl IntoExpr<'arena>) -> Expr<'arena> {
+        self.term(Term::Exponential(Number::E, e.into_expr(self).boxed()))
+    }
+
+    /// the unit step, 1 from where `e` is 0 on.
+    pub fn heaviside(self, e: impl IntoExpr<'arena>) -> Expr<'arena> {
+        self.term(Term::Heaviside(e.into_expr(self).boxed()))
+    }
+
+    /// the unit impulse where `e` is 0.
+    pub fn dirac(self, e: impl IntoExpr<'arena>) -> Expr<'arena> {
+        self.term(Term::Dirac(e.into_expr(self).boxed()))
+    }
+
+    /// `below` while `switch` is negative, `above` from then on.
+    pub fn piecewise(self, switch: impl IntoExpr<'arena>, below: impl IntoExpr<'arena>, above: impl IntoExpr<'arena>) -> Expr<'arena> {
+        self.term(Term::Piecewise {
+            switch: switch.into_expr(self).boxed(),
+            below: below.into_expr(self).boxed(),
+            above: above.into_expr(self).boxed(),
+        })
+    }
+
+    /// the `order`th derivative of `e` with respect to `wrt`.
+    pub fn derivative(self, e: impl IntoExpr<'arena>, wrt: Name, order: usize) -> Expr<'arena> {
+        self.term(Term::Derivative {
+            order,
+            wrt: self.term(Term::Var(wrt)).boxed(),
+            term: e.into_expr(self).boxed(),
+        })
+    }
+
+    /// an antiderivative of `e` with respect to `wrt`, left unevaluated.
+    pub fn integral(self, e: impl IntoExpr<'arena>, wrt: Name) -> Expr<'arena> {
+        self.term(Term::Integral {
+            wrt: self.term(Term::Var(wrt)).boxed(),
+            term: e.into_expr(self).boxed(),
+        })
+    }
+}
+
+impl<'arena> Expr<'arena> {
+    pub fn as_term(self) -> &'arena Term<'arena> {
+        self.term
+    }
+
+    /// a fresh copy of the term, for the `ArenaTerm` based api.
+    pub fn boxed(self) -> ArenaTerm<'arena> {
+        ArenaTerm::new_in(self.term.clone(), self.ctx.arena)
+    }
+
+    /// `self^exponent`, becoming an `Exponential` when `self` is a number or `e`.
+    pub fn pow(self, exponent: impl IntoExpr<'arena>) -> Expr<'arena> {
+        let exponent = exponent.into_expr(self.ctx).boxed();
+
+        self.ctx.term(match self.term {
+            Term::Constant(base) => Term::Exponential(*base, exponent),
+            Term::E => Term::Exponential(Number::E, exponent),
+            _ => Term::Power { base: self.boxed(), exponent },
+        })
+    }
+
+    /// `self'`, `self''`, ... with respect to `wrt`.
+    pub fn derivative(self, wrt: Name, order: usize) -> Expr<'arena> {
+        self.ctx.derivative(self, wrt, order)
+    }
+}
+
+impl<'arena> From<Expr<'arena>> for Term<'arena> {
+    fn from(e: Expr<'arena>) -> Self {
+        e.term.clone()
+    }
+}
+
+impl fmt::Display for Expr<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.term)
+    }
+}
+
+impl<'arena> IntoExpr<'arena> for Expr<'arena> {
+    fn into_expr(self, _: Context<'arena>) -> Expr<'arena> {
+        self
+    }
+}
+
+impl<'arena> IntoExpr<'arena> for f64 {
+    fn into_expr(self, ctx: Context<'arena>) -> Expr<'arena> {
+        ctx.constant(self)
+    }
+}
+
+impl<'arena> IntoExpr<'arena> for i32 {
+    fn into_expr(self, ctx: Context<'arena>) -> Expr<'arena> {
+        ctx.constant(self)
+    }
+}
+
+impl<'arena> IntoExpr<'arena> for Number {
+    fn into_expr(self, ctx: Context<'arena>) -> Expr<'arena> {
+        ctx.constant(self)
+    }
+}
+
+impl<'arena, R: IntoExpr<'arena>> Add<R> for Expr<'arena> {
+    type Output = Expr<'arena>;
+
+    fn add(self, rhs: R) -> Expr<'arena> {
+        self.ctx.term(Term::Sum(self.boxed(), rhs.into_expr(self.ctx).boxed()))
+    }
+}
+
+/* a - b = a + (-b), the same tree `Term::parse_in` builds */
+impl<'arena, R: IntoExpr<'arena>> Sub<R> for Expr<'arena> {
+    type Output = Expr<'arena>;
+
+    fn sub(self, rhs: R) -> Expr<'arena> {
+        let rhs = rhs.into_expr(self.ctx);
+        self.ctx.term(Term::Sum(self.boxed(), ArenaTerm::new_in(Term::Negation(rhs.boxed()), self.ctx.arena)))
+    }
+}
+
+/* numbers scale, anything else multiplies */
+impl<'arena, R: IntoExpr<'arena>> Mul<R> for Expr<'arena> {
+    type Output = Expr<'arena>;
+
+    fn mul(self, rhs: R) -> Expr<'arena> {
+        use Term::*;
+
+        let rhs = rhs.into_expr(self.ctx);
+
+        self.ctx.term(match (self.term, rhs.term) {
+            (Constant(coefficient), _) => Scale { coefficient: *coefficient, term: rhs.boxed() },
+            (_, Constant(coefficient)) => Scale { coefficient: *coefficient, term: self.boxed() },
+            _ => Product(self.boxed(), rhs.boxed()),
+        })
+    }
+}
+
+impl<'arena, R: IntoExpr<'arena>> Div<R> for Expr<'arena> {
+    type Output = Expr<'arena>;
+
+    fn div(self, rhs: R) -> Expr<'arena> {
+        use Term::*;
+
+        let rhs = rhs.into_expr(self.ctx);
+
+        self.ctx.term(match (self.term, rhs.term) {
+            (Constant(numerator), Constant(denominator)) if !denominator.is_zero() => Constant(*numerator / *denominator),
+            (Constant(one), _) if one.is_one() => Reciprocal(rhs.boxed()),
+            _ => Quotient(self.boxed(), rhs.boxed()),
+        })
+    }
+}
+
+impl<'arena> Neg for Expr<'arena> {
+    type Output = Expr<'arena>;
+
+    fn neg(self) -> Expr<'arena> {
+        self.ctx.term(match self.term {
+            Term::Constant(c) => Term::Constant(-*c),
+            _ => Term::Negation(self.boxed()),
+        })
+    }
+}
+
+/* `2.0 * x`, `1 - x`, ... */
+macro_rules! number_on_the_left {
+    ($($number:ty),*) => {$(
+        impl<'arena> Add<Expr<'arena>> for $number {
+            type Output = Expr<'arena>;
+
+            fn add(self, rhs: Expr<'arena>) -> Expr<'arena> {
+                self.into_expr(rhs.ctx) + rhs
+            }
+        }
+
+        impl<'arena> Sub<Expr<'arena>> for $number {
+            type Output = Expr<'arena>;
+
+            fn sub(self, rhs: Expr<'arena>) -> Expr<'arena> {
+                self.into_expr(rhs.ctx) - rhs
+            }
+        }
+
+        impl<'arena> Mul<Expr<'arena>> for $number {
+            type Output = Expr<'arena>;
+
+            fn mul(self, rhs: Expr<'arena>) -> Expr<'arena> {
+                self.into_expr(rhs.ctx) * rhs
+            }
+        }
+
+        impl<'arena> Div<Expr<'arena>> for $number {
+            type Output = Expr<'arena>;
+
+            fn div(self, rhs: Expr<'arena>) -> Expr<'arena> {
+                self.into_expr(rhs.ctx) / rhs
+            }
+        }
+    )*};
+}
+
+number_on_the_left!(f64, i32, Number);
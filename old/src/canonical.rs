@@ -0,0 +1,413 @@
+use crate::{Term, ArenaTerm, LadesError, Number};
+use crate::simplify::{checked_powf, folded_powf, checked_ln};
+use bumpalo::Bump;
+use std::cmp::Ordering;
+
+/* in canonical form
+ *  - sums and products are nested to the left and sorted by `canonical_cmp`.
+ *  - a sum has at most one constant, as its last term, and no two terms that only differ by a coefficient.
+ *  - a product has no two factors with the same base, and at most one numeric coefficient, as a `Scale`
+ *    around it. numeric coefficients distribute over sums.
+ *  - subterms without variables are folded into constants.
+ * so terms that only differ by these rearrangements compare equal. */
+
+impl<'arena> Term<'arena> {
+    pub fn canonicalize_in(self, arena: &'arena Bump) -> Result<ArenaTerm<'arena>, LadesError> {
+        Ok(ArenaTerm::new_in(self.canonical(arena)?, arena))
+    }
+
+    fn canonical(self, arena: &'arena Bump) -> Result<Term<'arena>, LadesError> {
+        use Term::*;
+
+        let new_in = |term| ArenaTerm::new_in(term, arena);
+
+        Ok(match self {
+            Constant(_) | Var(_) | Symbol(_) | Pi | E | I => self,
+
+            Sum(..) => {
+                let mut terms = Terms::default();
+                terms.collect(self, Number::from(1), arena)?;
+                terms.build(arena)
+            },
+
+            Scale { .. } | Product(..) | Power { .. } | Exponential(..) => {
+                let mut factors = Factors::new();
+                factors.collect(self, Number::from(1), arena)?;
+
+                match factors.build(arena)? {
+                    /* c(a + b) = ca + cb */
+                    Scale { coefficient, term } if matches!(*term, Sum(..)) => {
+                        let mut terms = Terms::default();
+                        terms.collect_scaled(*term, coefficient);
+                        terms.build(arena)
+                    },
+                    product => product,
+                }
+            },
+
+            Quotient(..) | Negation(_) | Reciprocal(_) => self.lowered(arena).canonical(arena)?,
+
+            Sin(term) => match term.canonical(arena)? {
+                Constant(c) => Constant(c.sin()),
+                term => Sin(new_in(term)),
+            },
+
+            Cos(term) => match term.canonical(arena)? {
+                Constant(c) => Constant(c.cos()),
+                term => Cos(new_in(term)),
+            },
+
+            Ln(term) => match term.canonical(arena)? {
+                Constant(c) => Constant(checked_ln(c)?),
+                E => Constant(Number::from(1)),
+
+                /* ln(b^g) = ln(b) g */
+                Exponential(base, term) => Scale { coefficient: checked_ln(base)?, term }.canonical(arena)?,
+
+                term => Ln(new_in(term)),
+            },
+
+            Heaviside(term) => match term.canonical(arena)? {
+                Constant(c) => Constant(Number::from(if c.is_negative() { 0 } else { 1 })),
+                term => Heaviside(new_in(term)),
+            },
+
+            Dirac(term) => match term.canonical(arena)? {
+                Constant(c) if !c.is_zero() => Constant(Number::from(0)),
+                term => Dirac(new_in(term)),
+            },
+
+            Piecewise { switch, below, above } => match switch.canonical(arena)? {
+                Constant(c) if c.is_negative() => below.canonical(arena)?,
+                Constant(_) => above.canonical(arena)?,
+
+                switch => match (below.canonical(arena)?, above.canonical(arena)?) {
+                    (below, above) if below == above => below,
+                    (below, above) => Piecewise { switch: new_in(switch), below: new_in(below), above: new_in(above) },
+                },
+            },
+
+            Derivative { order, wrt, term } => match term.canonical(arena)? {
+                Constant(c) => Constant(if order == 0 { c } else { Number::from(0) }),
+                term if order == 0 => term,
+
+                /* any other variable is a function of `wrt` as far as we know, so it stays */
+                Var(x) if *wrt == Var(x) => Constant(Number::from(if order == 1 { 1 } else { 0 })),
+
+                term => Derivative { order, wrt: new_in(wrt.canonical(arena)?), term: new_in(term) },
+            },
+
+            Integral { wrt, term } => Integral { wrt: new_in(wrt.canonical(arena)?), term: new_in(term.canonical(arena)?) },
+        })
+    }
+
+    /// the total order operands of canonical sums and products are sorted by. powers sort right after their base.
+    pub fn canonical_cmp(&self, other: &Term) -> Ordering {
+        use Term::*;
+
+        if let (Power { .. }, _) | (_, Power { .. }) = (self, other) {
+            let (b1, e1) = self.base_and_exponent();
+            let (b2, e2) = other.base_and_exponent();
+
+            return b1.canonical_cmp(b2).then_with(|| match (e1, e2) {
+                (Some(e1), Some(e2)) => e1.canonical_cmp(e2),
+                (Some(e), None) => e.canonical_cmp(&Constant(Number::from(1))),
+                (None, Some(e)) => Constant(Number::from(1)).canonical_cmp(e),
+                (None, None) => Ordering::Equal,
+            });
+        }
+
+        match (self, other) {
+            (Constant(c1), Constant(c2)) => c1.total_cmp(c2),
+            (Var(v1), Var(v2)) => v1.cmp(v2),
+            (Symbol(s1), Symbol(s2)) => s1.cmp(s2),
+
+            (Sum(a1, b1), Sum(a2, b2)) | (Product(a1, b1), Product(a2, b2)) | (Quotient(a1, b1), Quotient(a2, b2)) => {
+                a1.canonical_cmp(a2).then_with(|| b1.canonical_cmp(b2))
+            },
+
+            (Scale { coefficient: c1, term: t1 }, Scale { coefficient: c2, term: t2 }) => {
+                t1.canonical_cmp(t2).then_with(|| c1.total_cmp(c2))
+            },
+
+            (Exponential(b1, e1), Exponential(b2, e2)) => b1.total_cmp(b2).then_with(|| e1.canonical_cmp(e2)),
+
+            (Negation(t1), Negation(t2)) | (Reciprocal(t1), Reciprocal(t2)) => t1.canonical_cmp(t2),
+            (Sin(t1), Sin(t2)) | (Cos(t1), Cos(t2)) | (Ln(t1), Ln(t2)) => t1.canonical_cmp(t2),
+            (Heaviside(t1), Heaviside(t2)) | (Dirac(t1), Dirac(t2)) => t1.canonical_cmp(t2),
+
+            (Piecewise { switch: s1, below: b1, above: a1 }, Piecewise { switch: s2, below: b2, above: a2 }) => {
+                s1.canonical_cmp(s2).then_with(|| b1.canonical_cmp(b2)).then_with(|| a1.canonical_cmp(a2))
+            },
+
+            (Derivative { order: o1, wrt: w1, term: t1 }, Derivative { order: o2, wrt: w2, term: t2 }) => {
+                t1.canonical_cmp(t2).then_with(|| w1.canonical_cmp(w2)).then_with(|| o1.cmp(o2))
+            },
+
+            (Integral { wrt: w1, term: t1 }, Integral { wrt: w2, term: t2 }) => t1.canonical_cmp(t2).then_with(|| w1.canonical_cmp(w2)),
+
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+
+    fn base_and_exponent(&self) -> (&Term<'arena>, Option<&Term<'arena>>) {
+        match self {
+            Term::Power { base, exponent } => (base, Some(exponent)),
+            _ => (self, None),
+        }
+    }
+
+    fn rank(&self) -> u8 {
+        use Term::*;
+
+        match self {
+            Constant(_) => 0,
+            Pi => 1,
+            E => 2,
+            I => 3,
+            Symbol(_) => 4,
+            Var(_) => 5,
+            Derivative { .. } => 6,
+            Power { .. } => 7,
+            Product(..) => 8,
+            Scale { .. } => 9,
+            Exponential(..) => 10,
+            Sin(_) => 11,
+            Cos(_) => 12,
+            Ln(_) => 13,
+            Heaviside(_) => 14,
+            Dirac(_) => 15,
+            Piecewise { .. } => 16,
+            Sum(..) => 17,
+            Negation(_) => 18,
+            Quotient(..) => 19,
+            Reciprocal(_) => 20,
+            Integral { .. } => 21,
+        }
+    }
+
+    /// `-a`, `a/b` and `1/a` in terms of `Scale` and `Power`, the encoding canonical form keeps them in.
+    /// any other term comes back as is.
+    pub(crate) fn lowered(self, arena: &'arena Bump) -> Term<'arena> {
+        use Term::*;
+
+        let reciprocal = |term| Power { base: term, exponent: ArenaTerm::new_in(Constant(Number::from(-1)), arena) };
+
+        match self {
+            Negation(term) => Scale { coefficient: Number::from(-1), term },
+            Quotient(numerator, denominator) => Product(numerator, ArenaTerm::new_in(reciprocal(denominator), arena)),
+            Reciprocal(term) => reciprocal(term),
+            term => term,
+        }
+    }
+}
+
+/// a flattened sum, as a constant plus coefficients of distinct terms.
+#[derive(Default)]
+struct Terms<'arena> {
+    constant: Number,
+    terms: Vec<(Number, Term<'arena>)>,
+}
+
+impl<'arena> Terms<'arena> {
+    fn collect(&mut self, term: Term<'arena>, scale: Number, arena: &'arena Bump) -> Result<(), LadesError> {
+        match term {
+            Term::Sum(t1, t2) => {
+                self.collect(*t1, scale, arena)?;
+                self.collect(*t2, scale, arena)
+            },
+
+            term => {
+                let term = term.canonical(arena)?;
+                self.collect_scaled(term, scale);
+                Ok(())
+            },
+        }
+    }
+
+    /// `term` has to be canonical already.
+    fn collect_scaled(&mut self, term: Term<'arena>, scale: Number) {
+        use Term::*;
+
+        match term {
+            Constant(c) => self.constant += scale * c,
+
+            Sum(t1, t2) => {
+                self.collect_scaled(*t1, scale);
+                self.collect_scaled(*t2, scale);
+            },
+
+            Scale { coefficient, term } => self.collect_scaled(*term, scale * coefficient),
+
+            term => match self.terms.iter_mut().find(|(_, like)| *like == term) {
+                Some((total, _)) => *total += scale,
+                None => self.terms.push((scale, term)),
+            },
+        }
+    }
+
+    fn build(mut self, arena: &'arena Bump) -> Term<'arena> {
+        use Term::*;
+
+        self.terms.retain(|(coefficient, _)| !coefficient.is_zero());
+        self.terms.sort_by(|(_, t1), (_, t2)| t1.canonical_cmp(t2));
+
+        let terms = self.terms.into_iter().map(|(coefficient, term)| match coefficient {
+            _ if coefficient.is_one() => term,
+            _ => Scale { coefficient, term: ArenaTerm::new_in(term, arena) },
+        });
+
+        let constant = (!self.constant.is_zero()).then_some(Constant(self.constant));
+
+        terms.chain(constant)
+            .reduce(|sum, term| Sum(ArenaTerm::new_in(sum, arena), ArenaTerm::new_in(term, arena)))
+            .unwrap_or(Constant(Number::from(0)))
+    }
+}
+
+/// a flattened product, as a coefficient times powers of distinct bases.
+struct Factors<'arena> {
+    coefficient: Number,
+    powers: Vec<(Term<'arena>, Term<'arena>)>,
+    exponentials: Vec<(Number, Term<'arena>)>,
+}
+
+impl<'arena> Factors<'arena> {
+    fn new() -> Self {
+        Factors { coefficient: Number::from(1), powers: Vec::new(), exponentials: Vec::new() }
+    }
+
+    /// collects `term^power`. `power` is always an integer, so `(ab)^power = a^power b^power`.
+    fn collect(&mut self, term: Term<'arena>, power: Number, arena: &'arena Bump) -> Result<(), LadesError> {
+        use Term::*;
+
+        match term {
+            Scale { coefficient, term } => {
+                self.coefficient *= checked_powf(coefficient, power)?;
+                self.collect(*term, power, arena)
+            },
+
+            Product(t1, t2) => {
+                self.collect(*t1, power, arena)?;
+                self.collect(*t2, power, arena)
+            },
+
+            Power { base, exponent } => match (base.canonical(arena)?, exponent.canonical(arena)?) {
+                (Constant(base), exponent) => self.collect(Exponential(base, ArenaTerm::new_in(exponent, arena)), power, arena),
+                (E, exponent) => self.collect(Exponential(Number::E, ArenaTerm::new_in(exponent, arena)), power, arena),
+
+                /* (a^m)^n = a^(mn) for integer n */
+                (base, Constant(n)) if n.to_integer().is_some() => self.collect_canonical(base, power * n, arena),
+
+                (base, exponent) => {
+                    let exponent = scaled(exponent, power, arena)?;
+                    self.push_power(base, exponent, arena);
+                    Ok(())
+                },
+            },
+
+            Exponential(base, exponent) => {
+                let exponent = scaled(exponent.canonical(arena)?, power, arena)?;
+
+                match self.exponentials.iter_mut().find(|(like, _)| *like == base) {
+                    Some((_, total)) => *total = Sum(ArenaTerm::new_in(total.clone(), arena), ArenaTerm::new_in(exponent, arena)),
+                    None => self.exponentials.push((base, exponent)),
+                }
+
+                Ok(())
+            },
+
+            term => {
+                let term = term.canonical(arena)?;
+                self.collect_canonical(term, power, arena)
+            },
+        }
+    }
+
+    fn collect_canonical(&mut self, term: Term<'arena>, power: Number, arena: &'arena Bump) -> Result<(), LadesError> {
+        use Term::*;
+
+        match term {
+            Constant(c) => {
+                self.coefficient *= checked_powf(c, power)?;
+                Ok(())
+            },
+
+            Scale { .. } | Product(..) | Power { .. } | Exponential(..) => self.collect(term, power, arena),
+
+            term => {
+                self.push_power(term, Constant(power), arena);
+                Ok(())
+            },
+        }
+    }
+
+    fn push_power(&mut self, base: Term<'arena>, exponent: Term<'arena>, arena: &'arena Bump) {
+        match self.powers.iter_mut().find(|(like, _)| *like == base) {
+            Some((_, total)) => *total = Term::Sum(ArenaTerm::new_in(total.clone(), arena), ArenaTerm::new_in(exponent, arena)),
+            None => self.powers.push((base, exponent)),
+        }
+    }
+
+    fn build(mut self, arena: &'arena Bump) -> Result<Term<'arena>, LadesError> {
+        use Term::*;
+
+        let mut factors = Vec::new();
+
+        for (base, exponent) in self.powers {
+            match exponent.canonical(arena)? {
+                Constant(c) if c.is_zero() => {},
+
+                /* i^2 = -1 */
+                Constant(c) if base == I && c.to_integer().is_some() => {
+                    let n = c.to_integer().unwrap().rem_euclid(4);
+
+                    if n >= 2 {
+                        self.coefficient = -self.coefficient;
+                    }
+
+                    if n % 2 == 1 {
+                        factors.push(I);
+                    }
+                },
+
+                Constant(c) if c.is_one() => factors.push(base),
+                exponent => factors.push(Power { base: ArenaTerm::new_in(base, arena), exponent: ArenaTerm::new_in(exponent, arena) }),
+            }
+        }
+
+        for (base, exponent) in self.exponentials {
+            match exponent.canonical(arena)? {
+                Constant(c) => match folded_powf(base, c)? {
+                    Some(power) => self.coefficient *= power,
+                    None => factors.push(Exponential(base, ArenaTerm::new_in(Constant(c), arena))),
+                },
+                _ if base.is_one() => {},
+                exponent => factors.push(Exponential(base, ArenaTerm::new_in(exponent, arena))),
+            }
+        }
+
+        if self.coefficient.is_zero() {
+            return Ok(Constant(Number::from(0)));
+        }
+
+        factors.sort_by(|f1, f2| f1.canonical_cmp(f2));
+
+        let product = factors.into_iter()
+            .reduce(|product, factor| Product(ArenaTerm::new_in(product, arena), ArenaTerm::new_in(factor, arena)));
+
+        Ok(match (self.coefficient, product) {
+            (coefficient, None) => Constant(coefficient),
+            (coefficient, Some(product)) if coefficient.is_one() => product,
+            (coefficient, Some(product)) => Scale { coefficient, term: ArenaTerm::new_in(product, arena) },
+        })
+    }
+}
+
+/// `power*exponent`, canonicalized.
+fn scaled<'arena>(exponent: Term<'arena>, power: Number, arena: &'arena Bump) -> Result<Term<'arena>, LadesError> {
+    match power {
+        _ if power.is_one() => Ok(exponent),
+        _ => Term::Scale { coefficient: power, term: ArenaTerm::new_in(exponent, arena) }.canonical(arena),
+    }
+}
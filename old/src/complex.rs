@@ -0,0 +1,293 @@
+use crate::{Term, ArenaTerm, LadesError, Number, Rewrite, Rules};
+use crate::simplify::checked_ln;
+use crate::trigonometry::{split, times};
+use bumpalo::Bump;
+use std::ops::{Add, Sub, Mul, Div, Neg};
+
+/// bare-bones complex number for the pole arithmetic, over `f64` or, to keep it exact, `Number`.
+/// terms spell complex values out with `Term::I`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct Complex<T = f64> {
+    pub re: T,
+    pub im: T,
+}
+
+impl Complex {
+    pub const ZERO: Complex = Complex { re: 0.0, im: 0.0 };
+    pub const ONE: Complex = Complex { re: 1.0, im: 0.0 };
+
+    pub fn new(re: f64, im: f64) -> Self {
+        Complex { re, im }
+    }
+
+    pub fn abs(self) -> f64 {
+        self.re.hypot(self.im)
+    }
+
+    pub fn arg(self) -> f64 {
+        self.im.atan2(self.re)
+    }
+
+    /// the principal square root.
+    pub fn sqrt(self) -> Self {
+        Complex::polar(self.abs().sqrt(), self.arg() / 2.0)
+    }
+
+    /// the principal cube root.
+    pub fn cbrt(self) -> Self {
+        Complex::polar(self.abs().cbrt(), self.arg() / 3.0)
+    }
+
+    /// `r*e^(i*theta)`
+    pub fn polar(r: f64, theta: f64) -> Self {
+        Complex { re: r * theta.cos(), im: r * theta.sin() }
+    }
+}
+
+impl From<f64> for Complex {
+    fn from(re: f64) -> Self {
+        Complex { re, im: 0.0 }
+    }
+}
+
+impl From<Number> for Complex<Number> {
+    fn from(re: Number) -> Self {
+        Complex { re, im: Number::from(0) }
+    }
+}
+
+impl<T: Add<Output = T>> Add for Complex<T> {
+    type Output = Complex<T>;
+
+    fn add(self, rhs: Complex<T>) -> Complex<T> {
+        Complex { re: self.re + rhs.re, im: self.im + rhs.im }
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Complex<T> {
+    type Output = Complex<T>;
+
+    fn sub(self, rhs: Complex<T>) -> Complex<T> {
+        Complex { re: self.re - rhs.re, im: self.im - rhs.im }
+    }
+}
+
+impl<T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T>> Mul for Complex<T> {
+    type Output = Complex<T>;
+
+    fn mul(self, rhs: Complex<T>) -> Complex<T> {
+        Complex {
+            re: self.re * rhs.re - self.im * rhs.im,
+            im: self.re * rhs.im + self.im * rhs.re,
+        }
+    }
+}
+
+impl<T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>> Div for Complex<T> {
+    type Output = Complex<T>;
+
+    fn div(self, rhs: Complex<T>) -> Complex<T> {
+        let norm = rhs.re * rhs.re + rhs.im * rhs.im;
+
+        Complex {
+            re: (self.re * rhs.re + self.im * rhs.im) / norm,
+            im: (self.im * rhs.re - self.re * rhs.im) / norm,
+        }
+    }
+}
+
+impl<T: Neg<Output = T>> Neg for Complex<T> {
+    type Output = Complex<T>;
+
+    fn neg(self) -> Complex<T> {
+        Complex { re: -self.re, im: -self.im }
+    }
+}
+
+impl<'arena> Term<'arena> {
+    /// the real part, taking variables, symbols and derivatives to be real.
+    pub fn real_part_in(&self, arena: &'arena Bump) -> Result<ArenaTerm<'arena>, LadesError> {
+        let (re, _) = self.parts(arena)?;
+        re.canonicalize_in(arena)?.simplify_in(arena)
+    }
+
+    /// the imaginary part, taking variables, symbols and derivatives to be real.
+    pub fn imaginary_part_in(&self, arena: &'arena Bump) -> Result<ArenaTerm<'arena>, LadesError> {
+        let (_, im) = self.parts(arena)?;
+        im.canonicalize_in(arena)?.simplify_in(arena)
+    }
+
+    pub fn contains_imaginary(&self) -> bool {
+        use Term::*;
+
+        match self {
+            I => true,
+            Constant(_) | Var(_) | Symbol(_) | Pi | E => false,
+
+            Sum(t1, t2) | Product(t1, t2) | Quotient(t1, t2) => t1.contains_imaginary() || t2.contains_imaginary(),
+            Power { base, exponent } => base.contains_imaginary() || exponent.contains_imaginary(),
+            Negation(term) | Reciprocal(term) | Scale { term, .. } | Exponential(_, term) => term.contains_imaginary(),
+            Sin(term) | Cos(term) | Ln(term) | Heaviside(term) | Dirac(term) => term.contains_imaginary(),
+            Piecewise { switch, below, above } => switch.contains_imaginary() || below.contains_imaginary() || above.contains_imaginary(),
+            Derivative { wrt, term, .. } | Integral { wrt, term } => wrt.contains_imaginary() || term.contains_imaginary(),
+        }
+    }
+
+    /// `(a, b)` with the term being `a + bi`, unsimplified but for powers, which multiply out in canonical form.
+    /// `Unsupported` where that would take a function other than the exponential of a complex argument.
+    fn parts(&self, arena: &'arena Bump) -> Result<(Term<'arena>, Term<'arena>), LadesError> {
+        use Term::*;
+
+        let new_in = |term| ArenaTerm::new_in(term, arena);
+        let sum = |a, b| Sum(new_in(a), new_in(b));
+
+        if !self.contains_imaginary() {
+            return Ok((self.clone(), Constant(Number::from(0))));
+        }
+
+        Ok(match self {
+            I => (Constant(Number::from(0)), Constant(Number::from(1))),
+
+            Sum(t1, t2) => {
+                let ((a, b), (c, d)) = (t1.parts(arena)?, t2.parts(arena)?);
+                (sum(a, c), sum(b, d))
+            },
+
+            Negation(term) => {
+                let (a, b) = term.parts(arena)?;
+                (Negation(new_in(a)), Negation(new_in(b)))
+            },
+
+            Scale { coefficient, term } => {
+                let (a, b) = term.parts(arena)?;
+                (Scale { coefficient: *coefficient, term: new_in(a) }, Scale { coefficient: *coefficient, term: new_in(b) })
+            },
+
+            Product(t1, t2) => times_parts(t1.parts(arena)?, t2.parts(arena)?, arena),
+            Quotient(t1, t2) => divided_parts(t1.parts(arena)?, t2.parts(arena)?, arena),
+            Reciprocal(term) => divided_parts((Constant(Number::from(1)), Constant(Number::from(0))), term.parts(arena)?, arena),
+
+            /* by repeated squaring, multiplying out each step so the parts don't double in size with every factor */
+            Power { base, exponent } => {
+                let n = match **exponent {
+                    Constant(n) => n.to_integer().ok_or_else(|| LadesError::Unsupported(self.to_string()))?,
+                    _ => return Err(LadesError::Unsupported(self.to_string())),
+                };
+
+                let canonical = |(a, b): (Term<'arena>, Term<'arena>)| -> Result<_, LadesError> {
+                    Ok((*a.canonicalize_in(arena)?, *b.canonicalize_in(arena)?))
+                };
+
+                let mut square = canonical(base.parts(arena)?)?;
+                let mut power = (Constant(Number::from(1)), Constant(Number::from(0)));
+
+                for k in 0..u128::BITS - n.unsigned_abs().leading_zeros() {
+                    if k > 0 {
+                        square = canonical(times_parts(square.clone(), square, arena))?;
+                    }
+
+                    if n.unsigned_abs() >> k & 1 == 1 {
+                        power = canonical(times_parts(power, square.clone(), arena))?;
+                    }
+                }
+
+                match n < 0 {
+                    true => divided_parts((Constant(Number::from(1)), Constant(Number::from(0))), power, arena),
+                    false => power,
+                }
+            },
+
+            /* b^(x + iy) = b^x (cos(y ln(b)) + i sin(y ln(b))) */
+            Exponential(base, exponent) => {
+                let (x, y) = exponent.parts(arena)?;
+                let magnitude = Exponential(*base, new_in(x));
+                let angle = new_in(times(checked_ln(*base)?, y, arena));
+
+                (Product(new_in(magnitude.clone()), new_in(Cos(angle.clone()))), Product(new_in(magnitude), new_in(Sin(angle))))
+            },
+
+            _ => return Err(LadesError::Unsupported(self.to_string())),
+        })
+    }
+}
+
+/// `(a + bi)(c + di) = (ac - bd) + (ad + bc)i`
+fn times_parts<'arena>((a, b): (Term<'arena>, Term<'arena>), (c, d): (Term<'arena>, Term<'arena>), arena: &'arena Bump) -> (Term<'arena>, Term<'arena>) {
+    use Term::*;
+
+    let new_in = |term| ArenaTerm::new_in(term, arena);
+    let product = |a, b| new_in(Product(new_in(a), new_in(b)));
+
+    (
+        Sum(product(a.clone(), c.clone()), new_in(Negation(product(b.clone(), d.clone())))),
+        Sum(product(a, d), product(b, c)),
+    )
+}
+
+/// `(a + bi)/(c + di) = ((ac + bd) + (bc - ad)i)/(c^2 + d^2)`
+fn divided_parts<'arena>((a, b): (Term<'arena>, Term<'arena>), (c, d): (Term<'arena>, Term<'arena>), arena: &'arena Bump) -> (Term<'arena>, Term<'arena>) {
+    use Term::*;
+
+    let new_in = |term| ArenaTerm::new_in(term, arena);
+    let product = |a, b| new_in(Product(new_in(a), new_in(b)));
+    let square = |x| new_in(Power { base: new_in(x), exponent: new_in(Constant(Number::from(2))) });
+    let norm = new_in(Sum(square(c.clone()), square(d.clone())));
+
+    (
+        Quotient(new_in(Sum(product(a.clone(), c.clone()), product(b.clone(), d.clone()))), norm.clone()),
+        Quotient(new_in(Sum(product(b, c), new_in(Negation(product(a, d))))), norm),
+    )
+}
+
+impl Rules {
+    /// adds (opt-in) euler's formula, turning `e^((-1 + 2i)t)` into `e^(-t)(cos(2t) + i sin(2t))`.
+    pub fn register_euler(&mut self) -> &mut Self {
+        self.register(Rewrite::new("euler", |term, arena| {
+            let Term::Exponential(_, exponent) = term else {
+                return Ok(None);
+            };
+
+            if !exponent.contains_imaginary() {
+                return Ok(None);
+            }
+
+            let (re, im) = match term.parts(arena) {
+                Ok(parts) => parts,
+                Err(LadesError::Unsupported(_)) => return Ok(None),
+                Err(error) => return Err(error),
+            };
+
+            Ok(Some(Term::Sum(
+                re.canonicalize_in(arena)?,
+                ArenaTerm::new_in(Term::Product(ArenaTerm::new_in(Term::I, arena), im.canonicalize_in(arena)?), arena),
+            )))
+        }))
+    }
+
+    /// adds (opt-in) euler's formula the other way around, turning `k cos(x) ± k i sin(x)` into `k e^(±ix)`.
+    pub fn register_exponential_form(&mut self) -> &mut Self {
+        use Term::*;
+
+        self.register(Rewrite::new("exponential form", |term, arena| {
+            let Sum(t1, t2) = term else {
+                return Ok(None);
+            };
+
+            /* canonical form gathers the coefficients, so `2i*sin(x)` splits into 2, i and sin(x) */
+            let (t1, t2) = (t1.clone().canonicalize_in(arena)?, t2.clone().canonicalize_in(arena)?);
+
+            Ok(match (split(&t1), split(&t2)) {
+                ((k1, None, Cos(x)), (k2, Some(I), Sin(y))) | ((k2, Some(I), Sin(y)), (k1, None, Cos(x))) if x == y && (k1 == k2 || k1 == -k2) => {
+                    let angle = Product(ArenaTerm::new_in(I, arena), x.clone());
+                    let exponent = match k1 == k2 {
+                        true => angle,
+                        false => Negation(ArenaTerm::new_in(angle, arena)),
+                    };
+
+                    Some(times(k1, Exponential(Number::E, ArenaTerm::new_in(exponent, arena)), arena))
+                },
+                _ => None,
+            })
+        }))
+    }
+}
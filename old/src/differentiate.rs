@@ -1,68 +1,185 @@
-use crate::{Term, ArenaTerm};
+use crate::{Term, ArenaTerm, LadesError, Number};
+use crate::simplify::checked_ln;
 use bumpalo::Bump;
 
 impl<'arena> Term<'arena> {
-    pub fn differentiate_in(self, wrt: Term, arena: &'arena Bump) -> ArenaTerm<'arena> {
+    pub fn differentiate_in(self, wrt: Term, arena: &'arena Bump) -> Result<ArenaTerm<'arena>, LadesError> {
         use Term::*;
 
-        if let Var(wrt) = wrt {
-            match *self.simplify_in(&arena) { /* try with debug_simplify() */
-                Constant(_) => ArenaTerm::new_in(Constant(0.0), &arena),
-                
-                Var(x) => match x {
-                    _ if x == wrt => ArenaTerm::new_in(Constant(1.0), &arena),
-                    _ => ArenaTerm::new_in(Constant(0.0), &arena)
-                },
+        let Var(wrt) = wrt else {
+            return Err(LadesError::NonVariable(wrt.to_string()));
+        };
 
-                Sum(t1, t2) => Sum(
-                    ArenaTerm::new_in(*t1.simplify_in(&arena).differentiate_in(Var(wrt), &arena), &arena),
-                    ArenaTerm::new_in(*t2.simplify_in(&arena).differentiate_in(Var(wrt), &arena), &arena)
-                ).simplify_in(&arena),
+        Ok(match *self.simplify_in(arena)? { /* try with debug_simplify() */
+            Constant(_) | Symbol(_) | Pi | E | I => ArenaTerm::new_in(Constant(Number::from(0)), arena),
+            
+            Var(x) => match x {
+                _ if x == wrt => ArenaTerm::new_in(Constant(Number::from(1)), arena),
+                _ => ArenaTerm::new_in(Constant(Number::from(0)), arena)
+            },
 
-                Scale { coefficient, term } => Scale {
-                    coefficient: coefficient,
-                    term: ArenaTerm::new_in(*term.differentiate_in(Var(wrt), &arena), &arena)
-                }.simplify_in(&arena),
+            Sum(t1, t2) => Sum(
+                ArenaTerm::new_in(*t1.simplify_in(arena)?.differentiate_in(Var(wrt), arena)?, arena),
+                ArenaTerm::new_in(*t2.simplify_in(arena)?.differentiate_in(Var(wrt), arena)?, arena)
+            ).simplify_in(arena)?,
 
-                Product(t1, t2) => Sum(
-                    Product(
-                        t1.clone().differentiate_in(Var(wrt), &arena),
-                        t2.clone()
-                    ).simplify_in(&arena),
+            Scale { coefficient, term } => Scale {
+                coefficient,
+                term: ArenaTerm::new_in(*term.differentiate_in(Var(wrt), arena)?, arena)
+            }.simplify_in(arena)?,
+
+            Product(t1, t2) => Sum(
+                Product(
+                    t1.clone().differentiate_in(Var(wrt), arena)?,
+                    t2.clone()
+                ).simplify_in(arena)?,
 
+                Product(
+                    t1,
+                    t2.differentiate_in(Var(wrt), arena)?
+                ).simplify_in(arena)?,
+            ).simplify_in(arena)?,
+
+            Power { base, exponent } => match *exponent {
+                Constant(c) => ArenaTerm::new_in(
                     Product(
-                        t1,
-                        t2.differentiate_in(Var(wrt), &arena)
-                    ).simplify_in(&arena),
-                ).simplify_in(&arena),
-
-                Power { base, exponent } => match *exponent {
-                    Constant(c) => ArenaTerm::new_in(
-                        Product(
-                            ArenaTerm::new_in(Scale {
-                                coefficient: c,
-                                term: ArenaTerm::new_in(Power {
-                                    base: base.clone(),
-                                    exponent: ArenaTerm::new_in(Constant(c - 1.0), &arena)
-                                }, &arena)
-                            }, &arena),
-
-                            base.differentiate_in(Var(wrt), &arena)
-                        ),
-                    &arena),
-
-                    _ => todo!("requires natural logarithmic terms"),
+                        ArenaTerm::new_in(Scale {
+                            coefficient: c,
+                            term: ArenaTerm::new_in(Power {
+                                base: base.clone(),
+                                exponent: ArenaTerm::new_in(Constant(c - Number::from(1)), arena)
+                            }, arena)
+                        }, arena),
+
+                        base.differentiate_in(Var(wrt), arena)?
+                    ),
+                arena),
+
+                /* d/dx f^g = d/dx e^(g ln(f)) = f^g (g' ln(f) + g f'/f) */
+                exponent => {
+                    let exponent = ArenaTerm::new_in(exponent, arena);
+
+                    ArenaTerm::new_in(Product(
+                        ArenaTerm::new_in(Power { base: base.clone(), exponent: exponent.clone() }, arena),
+                        ArenaTerm::new_in(Sum(
+                            ArenaTerm::new_in(Product(
+                                exponent.clone().differentiate_in(Var(wrt), arena)?,
+                                ArenaTerm::new_in(Ln(base.clone()), arena)
+                            ), arena),
+
+                            ArenaTerm::new_in(Product(
+                                exponent,
+                                ArenaTerm::new_in(Product(
+                                    base.clone().differentiate_in(Var(wrt), arena)?,
+                                    ArenaTerm::new_in(Power {
+                                        base,
+                                        exponent: ArenaTerm::new_in(Constant(Number::from(-1)), arena)
+                                    }, arena)
+                                ), arena)
+                            ), arena)
+                        ), arena).simplify_in(arena)?
+                    ), arena)
                 },
+            },
+
+            Negation(term) => Negation(term.differentiate_in(Var(wrt), arena)?).simplify_in(arena)?,
+
+            /* d/dx f/g = (f'g - fg')/g^2 */
+            Quotient(f, g) => Quotient(
+                ArenaTerm::new_in(Sum(
+                    ArenaTerm::new_in(Product(f.clone().differentiate_in(Var(wrt), arena)?, g.clone()), arena),
+                    ArenaTerm::new_in(Negation(ArenaTerm::new_in(Product(f, g.clone().differentiate_in(Var(wrt), arena)?), arena)), arena)
+                ), arena),
+                ArenaTerm::new_in(Power { base: g, exponent: ArenaTerm::new_in(Constant(Number::from(2)), arena) }, arena)
+            ).simplify_in(arena)?,
+
+            /* d/dx 1/g = -g'/g^2 */
+            Reciprocal(g) => Negation(ArenaTerm::new_in(Quotient(
+                g.clone().differentiate_in(Var(wrt), arena)?,
+                ArenaTerm::new_in(Power { base: g, exponent: ArenaTerm::new_in(Constant(Number::from(2)), arena) }, arena)
+            ), arena)).simplify_in(arena)?,
+
+            /* d/dx b^g = ln(b) b^g g' */
+            Exponential(base, term) => Scale {
+                coefficient: checked_ln(base)?,
+                term: ArenaTerm::new_in(Product(
+                    ArenaTerm::new_in(Exponential(base, term.clone()), arena),
+                    term.differentiate_in(Var(wrt), arena)?
+                ), arena)
+            }.simplify_in(arena)?,
+
+            /* d/dx ln(g) = g'/g */
+            Ln(term) => Product(
+                term.clone().differentiate_in(Var(wrt), arena)?,
+                ArenaTerm::new_in(Power {
+                    base: term,
+                    exponent: ArenaTerm::new_in(Constant(Number::from(-1)), arena)
+                }, arena)
+            ).simplify_in(arena)?,
+
+            Sin(term) => ArenaTerm::new_in(Product(
+                ArenaTerm::new_in(Cos(term.clone()), arena),
+                term.differentiate_in(Var(wrt), arena)?
+            ), arena),
+
+            /* d/dx cos(g) = -sin(g) g' */
+            Cos(term) => Scale {
+                coefficient: Number::from(-1),
+                term: ArenaTerm::new_in(Product(
+                    ArenaTerm::new_in(Sin(term.clone()), arena),
+                    term.differentiate_in(Var(wrt), arena)?
+                ), arena)
+            }.simplify_in(arena)?,
+
+            /* d/dx u(g) = δ(g) g' */
+            Heaviside(term) => Product(
+                ArenaTerm::new_in(Dirac(term.clone()), arena),
+                term.differentiate_in(Var(wrt), arena)?
+            ).simplify_in(arena)?,
+
+            /* δ'(g) has no other form, so it stays a derivative */
+            Dirac(term) => match term.contains_var(wrt) {
+                true => ArenaTerm::new_in(Derivative {
+                    order: 1,
+                    wrt: ArenaTerm::new_in(Var(wrt), arena),
+                    term: ArenaTerm::new_in(Dirac(term), arena)
+                }, arena),
+                false => ArenaTerm::new_in(Constant(Number::from(0)), arena),
+            },
+
+            /* each piece differentiates on its own, plus an impulse the size of the jump where the switch crosses 0 */
+            Piecewise { switch, below, above } => Sum(
+                ArenaTerm::new_in(Piecewise {
+                    switch: switch.clone(),
+                    below: below.clone().differentiate_in(Var(wrt), arena)?,
+                    above: above.clone().differentiate_in(Var(wrt), arena)?,
+                }, arena),
+
+                ArenaTerm::new_in(Product(
+                    ArenaTerm::new_in(Product(
+                        ArenaTerm::new_in(Sum(above, ArenaTerm::new_in(Scale { coefficient: Number::from(-1), term: below }, arena)), arena),
+                        ArenaTerm::new_in(Dirac(switch.clone()), arena)
+                    ), arena),
+                    switch.differentiate_in(Var(wrt), arena)?
+                ), arena)
+            ).simplify_in(arena)?,
+
+            Derivative { order, wrt: inner_wrt, term } => match *inner_wrt {
+                Var(x) if x == wrt => ArenaTerm::new_in(Derivative { order: order + 1, wrt: inner_wrt, term }, arena),
 
-                Sin(term) => ArenaTerm::new_in(Product(
-                    ArenaTerm::new_in(Cos(term.clone()), &arena),
-                    term.differentiate_in(Var(wrt), &arena)
-                ), &arena),
+                /* partial derivatives commute, so differentiate what's inside instead */
+                _ => Derivative {
+                    order,
+                    wrt: inner_wrt,
+                    term: term.differentiate_in(Var(wrt), arena)?
+                }.simplify_in(arena)?,
+            },
 
-                _ => todo!("didn't implement differentiation fully")
-            }
-        } else {
-            todo!("can't differentiate with respect to a non-variable term as of now.")
-        }
+            /* the fundamental theorem of calculus */
+            Integral { wrt: inner_wrt, term } => match *inner_wrt {
+                Var(x) if x == wrt => term,
+                _ => Integral { wrt: inner_wrt, term: term.differentiate_in(Var(wrt), arena)? }.simplify_in(arena)?,
+            },
+        })
     }
 }
@@ -1,47 +1,175 @@
 use std::fmt;
-use crate::Term;
+use crate::{Term, Equation, Number};
+use crate::parse::{is_subscript, FUNCTIONS};
+use crate::name::name_length;
 
 impl std::fmt::Display for Term<'_> {
+    /* parenthesizes just enough for `Term::parse_in` to read the output back into the same tree */
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         use Term::*;
 
         match self {
             Constant(n) => write!(f, "{n}"),
             Var(c) => write!(f, "{c}"),
-            Sum(t1, t2) => write!(f, "{t1} + {t2}"),
+            Pi => write!(f, "π"),
+            E => write!(f, "e"),
+            I => write!(f, "i"),
 
-            Scale { coefficient, term } => match *term.clone() {
-                Sum(s1, s2) => write!(f, "{coefficient}({s1} + {s2})"),
-                Scale { coefficient: coefficient2, term: term2 } => write!(f, "{coefficient}*{coefficient2}*{}", term2),
-                _ => write!(f, "{coefficient}*{term}")
+            /* single ascii letters are variables, so only greek-like names go without braces */
+            Symbol(name) => {
+                let mut chars = name.chars();
+
+                match chars.next() {
+                    Some(c) if c.is_alphabetic() && !c.is_ascii() && c != 'π' && chars.all(is_subscript) => write!(f, "{name}"),
+                    _ => write!(f, "{{{name}}}"),
+                }
+            },
+
+            Sum(t1, t2) => match &**t2 {
+                Negation(term) if matches!(**term, Sum(..)) => write!(f, "{t1} - ({term})"),
+                Negation(term) => write!(f, "{t1} - {term}"),
+                Sum(..) => write!(f, "{t1} + ({t2})"),
+                _ => write!(f, "{t1} + {t2}"),
             },
 
-            Product(t1, t2) => match (*t1.clone(), *t2.clone()) {
-                (Var(c), Sum(s1, s2)) => write!(f, "{c}({s1} + {s2})"),
-                (Sum(s1, s2), Var(c)) => write!(f, "({s1} + {s2}){c}"),
-                (t1, Scale { coefficient, term }) => write!(f, "{coefficient}{t1}{term}"),
-                (_, _) => write!(f, "{t1}{t2}")
+            Scale { coefficient, term } => match **term {
+                Sum(..) | Negation(..) => write!(f, "{coefficient}*({term})"),
+                _ => write!(f, "{coefficient}*{term}"),
+            },
+
+            Product(t1, t2) => {
+                let left = match **t1 {
+                    Sum(..) | Scale { .. } | Quotient(..) | Reciprocal(..) => format!("({t1})"),
+                    _ => t1.to_string(),
+                };
+
+                write!(f, "{left}")?;
+
+                let right = t2.to_string();
+                let right = match **t2 {
+                    Sum(..) | Scale { .. } | Product(..) | Quotient(..) => format!("({right})"),
+                    _ if right.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '.') => format!("({right})"),
+                    _ => right,
+                };
+
+                /* juxtaposition would glue numbers together, names like `x_1` and `y` into `x_1y`, or letters
+                   and a parenthesis into a call like `ln(x + 1)` */
+                match right.starts_with('(') {
+                    true if FUNCTIONS.iter().any(|name| left.ends_with(name)) => write!(f, "*{right}"),
+                    true => write!(f, "{right}"),
+                    false if !juxtaposable(&left, &right) => write!(f, "*{right}"),
+                    false => write!(f, "{right}"),
+                }
+            },
+
+            Quotient(numerator, denominator) => {
+                match **numerator {
+                    Sum(..) | Scale { .. } => write!(f, "({numerator})/")?,
+                    _ => write!(f, "{numerator}/")?,
+                }
+
+                denominator.fmt_denominator(f)
             },
 
-            Power { base, exponent } => match (*base.clone(), *exponent.clone()) {
-                (Sum(s1, s2), Sum(s3, s4)) => write!(f, "({s1} + {s2})^({s3} + {s4})"),
-                (Sum(s1, s2), exponent) => write!(f, "({s1} + {s2})^{exponent}"),
-                (base, Sum(s1, s2)) => write!(f, "{base}^({s1} + {s2})"),
-                (Product(s1, s2), exponent) => write!(f, "({s1} * {s2})^{exponent}"),
-                (base, Product(s1, s2)) => write!(f, "{base}^({s1} * {s2})"),
-                (base, exponent) => write!(f, "{base}^{exponent}")
+            Negation(term) => match **term {
+                Sum(..) | Scale { .. } | Product(..) | Quotient(..) => write!(f, "-({term})"),
+                _ => write!(f, "-{term}"),
             },
 
-            Exponential(base, t) => match *t.clone() {
-                Sum(s1, s2) => write!(f, "{base}^({s1} + {s2})"),
-                Product(s1, s2) => write!(f, "{base}^({s1} * {s2})"),
-                _ => write!(f, "{base}^{}", **t)
+            Reciprocal(term) => {
+                write!(f, "1/")?;
+                term.fmt_denominator(f)
+            },
+
+            Power { base, exponent } => {
+                match **base {
+                    Sum(..) | Product(..) | Scale { .. } | Power { .. } | Exponential(..) => write!(f, "({base})")?,
+                    Quotient(..) | Negation(..) | Reciprocal(..) => write!(f, "({base})")?,
+                    Constant(c) if c.is_negative() || c.is_fraction() => write!(f, "({base})")?,
+                    _ => write!(f, "{base}")?,
+                }
+
+                write!(f, "^")?;
+                exponent.fmt_exponent(f)
+            },
+
+            Exponential(base, t) => {
+                match *base {
+                    _ if *base == Number::E => write!(f, "e^")?,
+                    _ if base.is_negative() || base.is_fraction() => write!(f, "({base})^")?,
+                    _ => write!(f, "{base}^")?,
+                }
+
+                t.fmt_exponent(f)
             },
 
             Sin(t) => write!(f, "sin({t})"),
             Cos(t) => write!(f, "cos({t})"),
+            Ln(t) => write!(f, "ln({t})"),
+
+            Heaviside(t) => write!(f, "heaviside({t})"),
+            Dirac(t) => write!(f, "dirac({t})"),
+            Piecewise { switch, below, above } => write!(f, "piecewise({switch}, {below}, {above})"),
 
             Derivative { order, wrt, term } => write!(f, "({term}, wrt: {}){}", wrt, "\'".repeat(*order)),
+            Integral { wrt, term } => write!(f, "integral({term}, wrt: {wrt})"),
         }
     }
 }
+
+impl Term<'_> {
+    fn fmt_exponent(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Term::Sum(..) | Term::Product(..) | Term::Scale { .. } => write!(f, "({self})"),
+            Term::Quotient(..) | Term::Negation(..) | Term::Reciprocal(..) => write!(f, "({self})"),
+            Term::Constant(c) if c.is_fraction() => write!(f, "({self})"),
+            _ => write!(f, "{self}"),
+        }
+    }
+
+    /* whatever follows a `/` is read up to the next operator */
+    fn fmt_denominator(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Term::*;
+
+        match self {
+            Sum(..) | Product(..) | Scale { .. } | Quotient(..) | Negation(..) | Reciprocal(..) => write!(f, "({self})"),
+            Constant(c) if c.is_negative() || c.is_fraction() => write!(f, "({self})"),
+            _ => write!(f, "{self}"),
+        }
+    }
+}
+
+/// whether `left` and `right` still read as two factors when written next to each other,
+/// i.e. no name reaches across the seam (`t` and `au` would read as `tau`).
+fn juxtaposable(left: &str, right: &str) -> bool {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+
+    let tail = &left[left.trim_end_matches(is_name_char).len()..];
+    let head = &right[..right.len() - right.trim_start_matches(is_name_char).len()];
+    let joined = format!("{tail}{head}");
+
+    let mut at = 0;
+    while at < tail.len() {
+        at += name_length(&joined[at..]).max(1);
+    }
+
+    at == tail.len()
+}
+
+impl std::fmt::Display for Equation<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn side(f: &mut fmt::Formatter, terms: &[Term]) -> fmt::Result {
+            match terms.split_first() {
+                None => write!(f, "0"),
+                Some((first, rest)) => {
+                    write!(f, "{first}")?;
+                    rest.iter().try_for_each(|term| write!(f, " + {term}"))
+                },
+            }
+        }
+
+        side(f, &self.lhs)?;
+        write!(f, " = ")?;
+        side(f, &self.rhs)
+    }
+}
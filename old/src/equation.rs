@@ -0,0 +1,257 @@
+use crate::{Term, ArenaTerm, LadesError, Number, Name, Polynomial};
+use bumpalo::Bump;
+use std::collections::HashMap;
+
+/// `lhs[0] + lhs[1] + ... = rhs[0] + rhs[1] + ...`
+#[derive(Clone)]
+pub struct Equation<'a> {
+    pub lhs: Vec<Term<'a>>,
+    pub rhs: Vec<Term<'a>>,
+}
+
+/// coupled equations over several unknowns sharing one time variable.
+#[derive(Clone)]
+pub struct System<'a> {
+    pub equations: Vec<Equation<'a>>,
+}
+
+/// one equation of a constant-coefficient linear system, rearranged into
+/// `sum_j sum_k (a_jk x_j^(k)) = forcing`, where `coefficients[j][k]` is `a_jk`.
+pub(crate) struct LinearOde<'a> {
+    pub coefficients: Vec<Vec<Number>>,
+    pub forcing: Vec<Term<'a>>,
+}
+
+impl<'a> Equation<'a> {
+    pub fn new(lhs: Vec<Term<'a>>, rhs: Vec<Term<'a>>) -> Self {
+        Equation { lhs, rhs }
+    }
+
+    /// solves the initial value problem for `unknown(wrt_time)`, with `initial_conditions[k]` being `y^(k)(0)`.
+    pub fn solve_ivp(
+        &self,
+        unknown: Term,
+        wrt_time: Term,
+        initial_conditions: &HashMap<usize, Number>,
+        arena: &'a Bump
+    ) -> Result<ArenaTerm<'a>, LadesError> {
+        let y = as_var(unknown)?;
+        let t = as_var(wrt_time)?;
+
+        let mut solutions = solve_linear(&[self], &[y], t, |_, k| initial_conditions.get(&k).copied(), arena)?;
+        Ok(solutions.remove(0))
+    }
+
+    pub(crate) fn linear_in(&self, unknowns: &[Name], t: Name, arena: &'a Bump) -> Result<LinearOde<'a>, LadesError> {
+        let mut ode = LinearOde { coefficients: vec![Vec::new(); unknowns.len()], forcing: Vec::new() };
+
+        for term in &self.lhs {
+            ode.collect(term, unknowns, t, Number::from(1), arena)?;
+        }
+
+        for term in &self.rhs {
+            ode.collect(term, unknowns, t, Number::from(-1), arena)?;
+        }
+
+        Ok(ode)
+    }
+}
+
+impl<'a> System<'a> {
+    pub fn new(equations: Vec<Equation<'a>>) -> Self {
+        System { equations }
+    }
+
+    /// solves for every unknown (in the order given), with `initial_conditions[("x", k)]` being `x^(k)(0)`.
+    pub fn solve_ivp(
+        &self,
+        unknowns: &[Term],
+        wrt_time: Term,
+        initial_conditions: &HashMap<(&str, usize), Number>,
+        arena: &'a Bump
+    ) -> Result<Vec<ArenaTerm<'a>>, LadesError> {
+        let unknowns = unknowns.iter().map(|x| as_var(x.clone())).collect::<Result<Vec<_>, _>>()?;
+        let t = as_var(wrt_time)?;
+        let equations: Vec<&Equation> = self.equations.iter().collect();
+
+        solve_linear(&equations, &unknowns, t, |x, k| initial_conditions.get(&(x.as_str(), k)).copied(), arena)
+    }
+}
+
+impl<'a> LinearOde<'a> {
+    /// adds `sign * term` (as it would appear on the left-hand side) to the equation.
+    fn collect(&mut self, term: &Term<'a>, unknowns: &[Name], t: Name, sign: Number, arena: &'a Bump) -> Result<(), LadesError> {
+        use Term::*;
+
+        if !unknowns.iter().any(|&x| term.contains_var(x)) {
+            self.forcing.push(Scale { coefficient: -sign, term: ArenaTerm::new_in(term.clone(), arena) });
+            return Ok(());
+        }
+
+        match term {
+            Sum(t1, t2) => {
+                self.collect(t1, unknowns, t, sign, arena)?;
+                self.collect(t2, unknowns, t, sign, arena)
+            },
+
+            Scale { coefficient, term } => self.collect(term, unknowns, t, sign * *coefficient, arena),
+            Negation(term) => self.collect(term, unknowns, t, -sign, arena),
+            Quotient(term, c) => match **c {
+                Constant(c) if !c.is_zero() => self.collect(term, unknowns, t, sign / c, arena),
+                _ => Err(LadesError::NonLinear(term.to_string())),
+            },
+
+            Product(t1, t2) => match (&**t1, &**t2) {
+                (Constant(c), term) | (term, Constant(c)) => self.collect(term, unknowns, t, sign * *c, arena),
+                _ => Err(LadesError::NonLinear(term.to_string())),
+            },
+
+            _ => {
+                let (j, k) = unknowns.iter()
+                    .enumerate()
+                    .find_map(|(j, &x)| Some((j, derivative_order(term, x, t)?)))
+                    .ok_or_else(|| LadesError::NonLinear(term.to_string()))?;
+
+                if self.coefficients[j].len() <= k {
+                    self.coefficients[j].resize(k + 1, Number::from(0));
+                }
+
+                self.coefficients[j][k] += sign;
+                Ok(())
+            },
+        }
+    }
+}
+
+/// transforms every equation, solves the resulting algebraic system in `s` with cramer's rule and
+/// transforms each unknown back.
+fn solve_linear<'a>(
+    equations: &[&Equation<'a>],
+    unknowns: &[Name],
+    t: Name,
+    initial_condition: impl Fn(Name, usize) -> Option<Number>,
+    arena: &'a Bump
+) -> Result<Vec<ArenaTerm<'a>>, LadesError> {
+    use Term::*;
+
+    if unknowns.is_empty() || equations.is_empty() {
+        return Err(LadesError::Singular);
+    }
+
+    let s = "spqz".chars().chain('a'..='z')
+        .filter_map(|c| Name::letter(c).ok())
+        .find(|s| *s != t && !unknowns.contains(s))
+        .ok_or_else(|| LadesError::Unsupported(format!("{} unknowns", unknowns.len())))?;
+
+    /* `sum(coefficients[k] s^k)` */
+    let polynomial = |coefficients: &[Number]| {
+        Polynomial::new_in(s, coefficients.iter().map(|&a| Constant(a)).collect(), arena).map(|p| p.to_term_in(arena))
+    };
+
+    let odes = equations.iter().map(|e| e.linear_in(unknowns, t, arena)).collect::<Result<Vec<_>, _>>()?;
+
+    if let Some(&x) = unknowns.iter().enumerate()
+        .find(|&(j, _)| odes.iter().all(|ode| ode.coefficients[j].iter().all(|a| a.is_zero())))
+        .map(|(_, x)| x)
+    {
+        return Err(LadesError::NoUnknown(x.to_string()));
+    }
+
+    let mut matrix = Vec::with_capacity(odes.len());
+    let mut rhs = Vec::with_capacity(odes.len());
+
+    for ode in odes {
+        /* L{x^(k)} = s^k X - sum(s^(k-1-m) x^(m)(0)), so the initial conditions end up next to the forcing */
+        let mut initial_terms = Vec::new();
+
+        for (j, coefficients) in ode.coefficients.iter().enumerate() {
+            for (k, &a) in coefficients.iter().enumerate().filter(|&(_, a)| !a.is_zero()) {
+                if initial_terms.len() < k {
+                    initial_terms.resize(k, Number::from(0));
+                }
+
+                for m in 0..k {
+                    let value = initial_condition(unknowns[j], m)
+                        .ok_or_else(|| LadesError::MissingInitialCondition(unknowns[j].to_string(), m))?;
+
+                    initial_terms[k - 1 - m] += a * value;
+                }
+            }
+        }
+
+        let mut b = polynomial(&initial_terms)?;
+
+        for term in ode.forcing {
+            b = ArenaTerm::new_in(Sum(b, term.laplace_in(Var(t), Var(s), arena)?), arena);
+        }
+
+        matrix.push(ode.coefficients.iter().map(|a| polynomial(a)).collect::<Result<Vec<_>, _>>()?);
+        rhs.push(b);
+    }
+
+    if matrix.len() != unknowns.len() {
+        return Err(LadesError::Singular);
+    }
+
+    let det = determinant(&matrix, arena);
+
+    if det.vanishes_in(s) {
+        return Err(LadesError::Singular);
+    }
+
+    /* X_j(s) = det(A with column j replaced by b) / det(A) */
+    (0..unknowns.len()).map(|j| {
+        let replaced: Vec<Vec<ArenaTerm>> = matrix.iter().zip(&rhs)
+            .map(|(row, b)| row.iter().enumerate().map(|(k, a)| if k == j { b.clone() } else { a.clone() }).collect())
+            .collect();
+
+        let transformed = Product(
+            determinant(&replaced, arena),
+            ArenaTerm::new_in(Power { base: det.clone(), exponent: ArenaTerm::new_in(Constant(Number::from(-1)), arena) }, arena),
+        );
+
+        transformed.inverse_laplace_in(Var(s), Var(t), arena)
+    }).collect()
+}
+
+/// cofactor expansion along the first row. the empty matrix has determinant 1.
+pub(crate) fn determinant<'a>(matrix: &[Vec<ArenaTerm<'a>>], arena: &'a Bump) -> ArenaTerm<'a> {
+    use Term::*;
+
+    if matrix.len() == 1 {
+        return matrix[0][0].clone();
+    }
+
+    (0..matrix.len()).map(|j| {
+        let minor: Vec<Vec<ArenaTerm>> = matrix[1..].iter()
+            .map(|row| row.iter().enumerate().filter(|&(k, _)| k != j).map(|(_, a)| a.clone()).collect())
+            .collect();
+
+        let cofactor = ArenaTerm::new_in(Product(matrix[0][j].clone(), determinant(&minor, arena)), arena);
+
+        match j % 2 {
+            0 => cofactor,
+            _ => ArenaTerm::new_in(Scale { coefficient: Number::from(-1), term: cofactor }, arena),
+        }
+    })
+    .reduce(|acc, cofactor| ArenaTerm::new_in(Sum(acc, cofactor), arena))
+    .unwrap_or_else(|| ArenaTerm::new_in(Constant(Number::from(1)), arena))
+}
+
+pub(crate) fn as_var(term: Term) -> Result<Name, LadesError> {
+    match term {
+        Term::Var(x) => Ok(x),
+        term => Err(LadesError::NonVariable(term.to_string())),
+    }
+}
+
+/// `k` if the term is the `k`th derivative of `x` with respect to `t`.
+fn derivative_order(term: &Term, x: Name, t: Name) -> Option<usize> {
+    use Term::*;
+
+    match term {
+        Var(v) if *v == x => Some(0),
+        Derivative { order, wrt, term } if **wrt == Var(t) => Some(order + derivative_order(term, x, t)?),
+        _ => None,
+    }
+}
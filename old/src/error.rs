@@ -0,0 +1,65 @@
+use std::fmt;
+use crate::{ParseError, EvalError};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LadesError {
+    /// no rule covers this term (yet).
+    Unsupported(String),
+    /// a variable was expected, e.g. as the differentiation target or the time variable.
+    NonVariable(String),
+    /// the term divides a nonzero quantity by zero.
+    DivisionByZero(String),
+    /// the term takes a function outside of its (real) domain, e.g. `ln(-1)`.
+    Domain(String),
+    /// a term the solver can't treat as a constant multiple of an unknown or one of its derivatives.
+    NonLinear(String),
+    /// the term isn't a polynomial in the variable it was asked about.
+    NonPolynomial(String),
+    /// the equations never mention this unknown.
+    NoUnknown(String),
+    /// the initial value of the given derivative of an unknown is needed but wasn't provided.
+    MissingInitialCondition(String, usize),
+    /// the equations don't determine the unknowns (too few of them, or linearly dependent).
+    Singular,
+    /// the rules still changed this term on their last allowed pass.
+    NotConverged(String),
+    /// the input isn't a well-formed term.
+    Parse(ParseError),
+    /// the term has no numeric value at the requested point.
+    Eval(EvalError),
+}
+
+impl fmt::Display for LadesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use LadesError::*;
+
+        match self {
+            Unsupported(t) => write!(f, "don't know how to handle {t}"),
+            NonVariable(t) => write!(f, "expected a variable, got the term {t}"),
+            DivisionByZero(t) => write!(f, "{t} divides by zero"),
+            Domain(t) => write!(f, "{t} is outside of its domain"),
+            NonLinear(t) => write!(f, "{t} isn't linear with constant coefficients in the unknowns"),
+            NonPolynomial(t) => write!(f, "{t} isn't a polynomial"),
+            NoUnknown(y) => write!(f, "the equations don't involve {y}"),
+            MissingInitialCondition(y, k) => write!(f, "missing the initial value of derivative {k} of {y}"),
+            Singular => write!(f, "the equations don't determine the unknowns"),
+            NotConverged(t) => write!(f, "the rules didn't settle on a form of {t}"),
+            Parse(e) => write!(f, "{e}"),
+            Eval(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for LadesError {}
+
+impl From<ParseError> for LadesError {
+    fn from(e: ParseError) -> Self {
+        LadesError::Parse(e)
+    }
+}
+
+impl From<EvalError> for LadesError {
+    fn from(e: EvalError) -> Self {
+        LadesError::Eval(e)
+    }
+}
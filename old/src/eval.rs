@@ -0,0 +1,115 @@
+use crate::{Term, Name, Number, LadesError};
+use crate::simplify;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EvalError {
+    /// the environment has no value for this variable.
+    Unbound(String),
+    /// a named parameter, which has to be substituted before evaluating.
+    UnboundSymbol(String),
+    /// the term divides by zero at this point.
+    DivisionByZero(String),
+    /// a function is taken outside of its (real) domain at this point, e.g. `ln(-1)`.
+    Domain(String),
+    /// a derivative of something other than a constant, which has no value without solving for it.
+    Derivative(String),
+    /// an integral the integrator couldn't find, which is only defined up to a constant anyway.
+    Integral(String),
+    /// the term involves `i`, so it has no real value. `real_part_in` and `imaginary_part_in` split it up.
+    Imaginary(String),
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use EvalError::*;
+
+        match self {
+            Unbound(var) => write!(f, "no value for {var}"),
+            UnboundSymbol(name) => write!(f, "no value for the parameter {name}"),
+            DivisionByZero(t) => write!(f, "{t} divides by zero"),
+            Domain(t) => write!(f, "{t} is outside of its domain"),
+            Derivative(t) => write!(f, "can't evaluate the derivative {t}"),
+            Integral(t) => write!(f, "can't evaluate the integral {t}"),
+            Imaginary(t) => write!(f, "{t} isn't real"),
+        }
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+impl Term<'_> {
+    /// the value of the term, with variables looked up in `env`.
+    pub fn eval(&self, env: &impl Fn(Name) -> Option<f64>) -> Result<f64, EvalError> {
+        use Term::*;
+
+        Ok(match self {
+            Constant(c) => c.to_f64(),
+            Var(var) => env(*var).ok_or_else(|| EvalError::Unbound(var.to_string()))?,
+            Symbol(name) => return Err(EvalError::UnboundSymbol(name.to_string())),
+            Pi => std::f64::consts::PI,
+            E => std::f64::consts::E,
+            I => return Err(EvalError::Imaginary(self.to_string())),
+
+            Sum(t1, t2) => t1.eval(env)? + t2.eval(env)?,
+            Scale { coefficient, term } => coefficient.to_f64() * term.eval(env)?,
+            Product(t1, t2) => t1.eval(env)? * t2.eval(env)?,
+            Negation(term) => -term.eval(env)?,
+            Quotient(t1, t2) => checked_div(t1.eval(env)?, t2.eval(env)?)?,
+            Reciprocal(term) => checked_div(1.0, term.eval(env)?)?,
+
+            Power { base, exponent } => checked_powf(base.eval(env)?, exponent.eval(env)?)?,
+            Exponential(base, exponent) => checked_powf(base.to_f64(), exponent.eval(env)?)?,
+
+            Sin(term) => term.eval(env)?.sin(),
+            Cos(term) => term.eval(env)?.cos(),
+
+            Ln(term) => match term.eval(env)? {
+                x if x <= 0.0 => return Err(EvalError::Domain(format!("ln({x})"))),
+                x => x.ln(),
+            },
+
+            Heaviside(term) => if term.eval(env)? < 0.0 { 0.0 } else { 1.0 },
+
+            Dirac(term) => match term.eval(env)? {
+                0.0 => return Err(EvalError::Domain("dirac(0)".to_string())),
+                _ => 0.0,
+            },
+
+            Piecewise { switch, below, above } => match switch.eval(env)? {
+                x if x < 0.0 => below.eval(env)?,
+                _ => above.eval(env)?,
+            },
+
+            /* only the trivial cases have a value without knowing the function */
+            Derivative { order: 0, term, .. } => term.eval(env)?,
+            Derivative { term, .. } if matches!(**term, Constant(_) | Symbol(_) | Pi | E | I) => 0.0,
+            Derivative { .. } => return Err(EvalError::Derivative(self.to_string())),
+            Integral { .. } => return Err(EvalError::Integral(self.to_string())),
+        })
+    }
+
+    /// the term's value at each of `points`, which `var` runs over. every other variable is looked up in `env`.
+    pub fn eval_over(&self, var: Name, points: &[f64], env: &impl Fn(Name) -> Option<f64>) -> Result<Vec<f64>, EvalError> {
+        points.iter()
+            .map(|&point| self.eval(&|v| if v == var { Some(point) } else { env(v) }))
+            .collect()
+    }
+}
+
+fn checked_div(numerator: f64, denominator: f64) -> Result<f64, EvalError> {
+    match denominator {
+        0.0 => Err(EvalError::DivisionByZero(format!("{numerator}/{denominator}"))),
+        _ => Ok(numerator / denominator),
+    }
+}
+
+/// `simplify`'s `checked_powf`, with its errors as `EvalError`s.
+fn checked_powf(base: f64, exponent: f64) -> Result<f64, EvalError> {
+    match simplify::checked_powf(Number::from(base), Number::from(exponent)) {
+        Ok(power) => Ok(power.to_f64()),
+        Err(LadesError::DivisionByZero(t)) => Err(EvalError::DivisionByZero(t)),
+        Err(LadesError::Domain(t)) => Err(EvalError::Domain(t)),
+        Err(e) => Err(EvalError::Domain(e.to_string())),
+    }
+}
@@ -0,0 +1,162 @@
+use crate::{Term, ArenaTerm, LadesError, Number, Name};
+use crate::simplify::checked_ln;
+use bumpalo::Bump;
+
+impl<'arena> Term<'arena> {
+    /// an antiderivative, without the constant. whatever has no closed form the rules below know of
+    /// stays as an `Integral`, after sums and constant factors have been pulled out of it.
+    pub fn integrate_in(self, wrt: Term, arena: &'arena Bump) -> Result<ArenaTerm<'arena>, LadesError> {
+        let Term::Var(x) = wrt else {
+            return Err(LadesError::NonVariable(wrt.to_string()));
+        };
+
+        let term = self.canonicalize_in(arena)?;
+        term.integral(x, arena)?.canonicalize_in(arena)?.simplify_in(arena)
+    }
+
+    fn integral(&self, x: Name, arena: &'arena Bump) -> Result<Term<'arena>, LadesError> {
+        use Term::*;
+
+        let new_in = |term| ArenaTerm::new_in(term, arena);
+
+        Ok(match self {
+            _ if !self.contains_var(x) => Product(new_in(self.clone()), new_in(Var(x))),
+
+            Sum(t1, t2) => Sum(new_in(t1.integral(x, arena)?), new_in(t2.integral(x, arena)?)),
+            Scale { coefficient, term } => Scale { coefficient: *coefficient, term: new_in(term.integral(x, arena)?) },
+            Negation(term) => Negation(new_in(term.integral(x, arena)?)),
+
+            Product(c, term) | Product(term, c) if !c.contains_var(x) => Product(c.clone(), new_in(term.integral(x, arena)?)),
+
+            term => match term.antiderivative(x, arena)? {
+                Some(antiderivative) => antiderivative,
+                None => Integral { wrt: new_in(Var(x)), term: new_in(term.clone()) },
+            },
+        })
+    }
+
+    /// the closed form for the basic functions of a linear argument, and for the products integration
+    /// by parts (or its cyclic variant) takes care of.
+    fn antiderivative(&self, x: Name, arena: &'arena Bump) -> Result<Option<Term<'arena>>, LadesError> {
+        use Term::*;
+
+        let new_in = |term| ArenaTerm::new_in(term, arena);
+        let scaled = |k: Number, term| Scale { coefficient: Number::from(1) / k, term: new_in(term) };
+
+        /* the slope of a linear argument, if it is one */
+        let slope = |term: &Term| term.linear_coefficients(x).map(|(k, _)| k).filter(|k| !k.is_zero());
+
+        Ok(match self {
+            Var(_) => Some(scaled(Number::from(2), Power { base: new_in(Var(x)), exponent: new_in(Constant(Number::from(2))) })),
+
+            Power { base, exponent } => match (&**base, &**exponent) {
+                /* sin^2(u) = (1 - cos(2u))/2, cos^2(u) = (1 + cos(2u))/2 */
+                (Sin(angle) | Cos(angle), Constant(n)) if *n == Number::from(2) => slope(angle).map(|k| {
+                    let sign = if matches!(**base, Sin(_)) { Number::from(-1) } else { Number::from(1) };
+                    let double = Scale { coefficient: Number::from(2), term: angle.clone() };
+
+                    Sum(
+                        new_in(scaled(Number::from(2), Var(x))),
+                        new_in(scaled(Number::from(4) * k * sign, Sin(new_in(double)))),
+                    )
+                }),
+
+                /* (kx + c)^n integrates to (kx + c)^(n+1)/(k(n+1)), or ln|kx + c|/k for n = -1, which without an
+                   absolute value is ln((kx + c)^2)/2k */
+                (_, Constant(n)) => slope(base).map(|k| match *n == Number::from(-1) {
+                    true => scaled(Number::from(2) * k, Ln(new_in(Power { base: base.clone(), exponent: new_in(Constant(Number::from(2))) }))),
+                    false => scaled(k * (*n + Number::from(1)), Power { base: base.clone(), exponent: new_in(Constant(*n + Number::from(1))) }),
+                }),
+
+                _ => None,
+            },
+
+            /* 1^g is just 1 */
+            Exponential(base, _) if base.is_one() => Some(Var(x)),
+            Exponential(base, exponent) => {
+                let ln = checked_ln(*base)?;
+                slope(exponent).map(|k| scaled(k * ln, self.clone()))
+            },
+            Sin(angle) => slope(angle).map(|k| scaled(-k, Cos(angle.clone()))),
+            Cos(angle) => slope(angle).map(|k| scaled(k, Sin(angle.clone()))),
+
+            Product(t1, t2) => match (&**t1, &**t2) {
+                /* sin(u)cos(u) = sin(2u)/2 */
+                (Sin(a1), Cos(a2)) | (Cos(a2), Sin(a1)) if a1 == a2 => slope(a1).map(|k| {
+                    scaled(Number::from(-4) * k, Cos(new_in(Scale { coefficient: Number::from(2), term: a1.clone() })))
+                }),
+
+                (Exponential(one, _), other) | (other, Exponential(one, _)) if one.is_one() => other.antiderivative(x, arena)?,
+
+                (exponential @ Exponential(..), trig @ (Sin(_) | Cos(_))) | (trig @ (Sin(_) | Cos(_)), exponential @ Exponential(..)) => {
+                    exponential_trig(exponential, trig, x, arena)?
+                },
+
+                /* by parts, differentiating the power of x until it's gone */
+                (power, other) | (other, power) if power.natural_power(x) => match other.antiderivative(x, arena)? {
+                    Some(antiderivative) => {
+                        let derivative = power.clone().differentiate_in(Var(x), arena)?;
+                        let rest = Product(derivative, new_in(antiderivative.clone())).canonicalize_in(arena)?.integral(x, arena)?;
+
+                        Some(Sum(
+                            new_in(Product(new_in(power.clone()), new_in(antiderivative))),
+                            new_in(Negation(new_in(rest))),
+                        ))
+                    },
+                    None => None,
+                },
+
+                _ => None,
+            },
+
+            _ => None,
+        })
+    }
+
+    /// `x` or `x^n` for a natural `n`.
+    fn natural_power(&self, x: Name) -> bool {
+        match self {
+            Term::Var(v) => *v == x,
+            Term::Power { base, exponent } => **base == Term::Var(x) && matches!(**exponent, Term::Constant(n) if n.is_positive() && n.to_integer().is_some()),
+            _ => false,
+        }
+    }
+}
+
+/// `e^(ax) sin(bx)` integrates to `e^(ax) (a sin(bx) - b cos(bx))/(a^2 + b^2)`,
+/// `e^(ax) cos(bx)` to `e^(ax) (a cos(bx) + b sin(bx))/(a^2 + b^2)`, intercepts or not.
+fn exponential_trig<'arena>(exponential: &Term<'arena>, trig: &Term<'arena>, x: Name, arena: &'arena Bump) -> Result<Option<Term<'arena>>, LadesError> {
+    use Term::*;
+
+    let new_in = |term| ArenaTerm::new_in(term, arena);
+
+    let (Exponential(base, exponent), Sin(angle) | Cos(angle)) = (exponential, trig) else {
+        return Ok(None);
+    };
+
+    let ln = checked_ln(*base)?;
+
+    let (Some((slope, _)), Some((b, _))) = (exponent.linear_coefficients(x), angle.linear_coefficients(x)) else {
+        return Ok(None);
+    };
+
+    let a = slope * ln;
+    let norm = a * a + b * b;
+
+    if a.is_zero() || b.is_zero() {
+        return Ok(None);
+    }
+
+    let (sin, cos) = (Sin(angle.clone()), Cos(angle.clone()));
+    let (first, second) = match trig {
+        Sin(_) => ((a, sin), (-b, cos)),
+        _ => ((a, cos), (b, sin)),
+    };
+
+    let combination = Sum(
+        new_in(Scale { coefficient: first.0 / norm, term: new_in(first.1) }),
+        new_in(Scale { coefficient: second.0 / norm, term: new_in(second.1) }),
+    );
+
+    Ok(Some(Product(new_in(exponential.clone()), new_in(combination))))
+}
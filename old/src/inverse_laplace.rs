@@ -0,0 +1,450 @@
+use crate::{Term, ArenaTerm, LadesError, Number, Name, Polynomial};
+use crate::complex::Complex;
+use bumpalo::Bump;
+use std::ops::{Add, Sub, Mul, Div};
+
+/// ratio of two polynomials in `s`, coefficients stored lowest power first. exact as long as the
+/// coefficients are.
+#[derive(Clone, PartialEq)]
+struct RationalFunction {
+    numerator: Vec<Number>,
+    denominator: Vec<Number>,
+}
+
+/// a pole and the coefficients of its terms in a partial fraction expansion.
+type Expansion = (Complex<Number>, Vec<Complex<Number>>);
+
+impl<'arena> Term<'arena> {
+    pub fn inverse_laplace_in(self, s_var: Term, wrt_time: Term, arena: &'arena Bump) -> Result<ArenaTerm<'arena>, LadesError> {
+        use Term::*;
+
+        let (s, t) = match (s_var, wrt_time) {
+            (Var(s), Var(t)) => (s, t),
+            (Var(_), t) => return Err(LadesError::NonVariable(t.to_string())),
+            (s, _) => return Err(LadesError::NonVariable(s.to_string())),
+        };
+
+        let term = self.simplify_in(arena)?;
+        let unsupported = || LadesError::Unsupported(term.to_string());
+
+        let mut inverse: Option<ArenaTerm> = None;
+
+        for (delay, fraction) in term.delayed_in(s).ok_or_else(unsupported)? {
+            for part in fraction.inverse_in(s, t, arena).ok_or_else(unsupported)? {
+                /* L^-1{e^(-as) F(s)} = u(t - a) f(t - a) */
+                let shifted = Sum(ArenaTerm::new_in(Var(t), arena), ArenaTerm::new_in(Constant(-delay), arena));
+
+                let part = match part {
+                    _ if delay.is_zero() => ArenaTerm::new_in(part, arena),
+
+                    /* an impulse is 0 before it happens anyway */
+                    Dirac(_) => part.substitute_in(t, &shifted, arena),
+                    Scale { ref term, .. } if matches!(**term, Dirac(_)) => part.substitute_in(t, &shifted, arena),
+
+                    _ => ArenaTerm::new_in(Product(
+                        ArenaTerm::new_in(Heaviside(ArenaTerm::new_in(shifted.clone(), arena)), arena),
+                        part.substitute_in(t, &shifted, arena),
+                    ), arena),
+                };
+
+                inverse = Some(match inverse {
+                    Some(inverse) => ArenaTerm::new_in(Sum(inverse, part), arena),
+                    None => part,
+                });
+            }
+        }
+
+        inverse.map_or_else(|| Ok(ArenaTerm::new_in(Constant(Number::from(0)), arena)), |inverse| inverse.simplify_in(arena))
+    }
+
+    /// whether the term is identically zero as a rational function of `s`.
+    pub(crate) fn vanishes_in(&self, s: Name) -> bool {
+        self.rational_in(s).is_some_and(|fraction| is_zero(&trim(&fraction.numerator)))
+    }
+
+    /// the term as `sum(e^(-as) F_a(s))` with rational `F_a`, as pairs of delays `a >= 0` and fractions.
+    fn delayed_in(&self, s: Name) -> Option<Vec<(Number, RationalFunction)>> {
+        use Term::*;
+
+        Some(match self {
+            /* b^(ks + c) = b^c e^(-as) with a = -k ln(b) */
+            Exponential(base, exponent) => {
+                let (slope, intercept) = exponent.linear_coefficients(s)?;
+                let delay = -slope * base.ln();
+
+                if delay.is_negative() {
+                    return None;
+                }
+
+                vec![(delay, RationalFunction::constant(base.pow(intercept)))]
+            },
+
+            Sum(t1, t2) => t2.delayed_in(s)?.into_iter().fold(t1.delayed_in(s)?, merge_delayed),
+            Product(t1, t2) => delayed_product(&t1.delayed_in(s)?, &t2.delayed_in(s)?),
+            Quotient(t1, t2) => delayed_product(&t1.delayed_in(s)?, &[(Number::from(0), t2.rational_in(s)?.powi(-1)?)]),
+
+            Negation(term) => term.delayed_in(s)?.into_iter()
+                .map(|(delay, fraction)| (delay, RationalFunction::constant(Number::from(-1)).mul(&fraction)))
+                .collect(),
+
+            Scale { coefficient, term } => term.delayed_in(s)?.into_iter()
+                .map(|(delay, fraction)| (delay, RationalFunction::constant(*coefficient).mul(&fraction)))
+                .collect(),
+
+            /* delays can't be divided by, but they can be raised to natural powers */
+            Power { base, exponent } => match (self.rational_in(s), &**exponent) {
+                (Some(fraction), _) => vec![(Number::from(0), fraction)],
+                (None, Constant(n)) => {
+                    let n = usize::try_from(n.to_integer()?).ok()?;
+                    let base = base.delayed_in(s)?;
+                    (0..n).fold(vec![(Number::from(0), RationalFunction::constant(Number::from(1)))], |power, _| delayed_product(&power, &base))
+                },
+                (None, _) => return None,
+            },
+
+            _ => vec![(Number::from(0), self.rational_in(s)?)],
+        })
+    }
+
+    /// the term as a ratio of polynomials in `s`, if it is one (with numeric coefficients).
+    fn rational_in(&self, s: Name) -> Option<RationalFunction> {
+        use Term::*;
+
+        match self {
+            Constant(c) => Some(RationalFunction::constant(*c)),
+            Var(v) if *v == s => Some(RationalFunction { numerator: vec![Number::from(0), Number::from(1)], denominator: vec![Number::from(1)] }),
+
+            Sum(t1, t2) => Some(t1.rational_in(s)?.add(&t2.rational_in(s)?)),
+            Product(t1, t2) => Some(t1.rational_in(s)?.mul(&t2.rational_in(s)?)),
+            Quotient(t1, t2) => Some(t1.rational_in(s)?.mul(&t2.rational_in(s)?.powi(-1)?)),
+            Negation(term) => Some(RationalFunction::constant(Number::from(-1)).mul(&term.rational_in(s)?)),
+            Reciprocal(term) => term.rational_in(s)?.powi(-1),
+            Scale { coefficient, term } => Some(RationalFunction::constant(*coefficient).mul(&term.rational_in(s)?)),
+
+            Power { base, exponent } => match **exponent {
+                Constant(n) => base.rational_in(s)?.powi(n.to_integer()?.try_into().ok()?),
+                _ => None,
+            },
+
+            Exponential(base, term) => match **term {
+                Constant(c) => Some(RationalFunction::constant(base.pow(c))),
+                _ => None,
+            },
+
+            _ => None,
+        }
+    }
+}
+
+impl RationalFunction {
+    fn constant(c: Number) -> Self {
+        RationalFunction { numerator: vec![c], denominator: vec![Number::from(1)] }
+    }
+
+    /// the inverse transform as a list of terms in `t`. `None` if there's a polynomial part beyond a constant,
+    /// which would need derivatives of the impulse.
+    fn inverse_in<'arena>(&self, s: Name, t: Name, arena: &'arena Bump) -> Option<Vec<Term<'arena>>> {
+        use Term::*;
+
+        let mut numerator = trim(&self.numerator);
+        let denominator = trim(&self.denominator);
+        let mut terms = Vec::new();
+
+        if numerator.len() > denominator.len() {
+            return None;
+        }
+
+        /* L^-1{c} = c δ(t), which leaves a proper fraction behind */
+        if !numerator.is_empty() && numerator.len() == denominator.len() {
+            let quotient = numerator[numerator.len() - 1] / denominator[denominator.len() - 1];
+            numerator = numerator.iter().zip(&denominator).map(|(&n, &d)| n - quotient * d).collect();
+            numerator.pop();
+
+            let impulse = Dirac(ArenaTerm::new_in(Var(t), arena));
+
+            terms.push(match tidied(quotient) {
+                quotient if quotient.is_one() => impulse,
+                quotient => Scale { coefficient: quotient, term: ArenaTerm::new_in(impulse, arena) },
+            });
+        }
+
+        let fraction = RationalFunction { numerator, denominator };
+
+        for (pole, coefficients) in fraction.partial_fractions(s, arena)? {
+            for (k, coefficient) in coefficients.into_iter().enumerate() {
+                /* A/(s - p)^(k+1) => A t^k e^(pt) / k! */
+                let parts = if pole.im.is_zero() {
+                    vec![(coefficient.re, time_factor(t, k, pole.re, None, arena))]
+                } else {
+                    /* the conjugate pole contributes the conjugate term, so only the real part survives (twice) */
+                    vec![
+                        (Number::from(2) * coefficient.re, time_factor(t, k, pole.re, Some(Cos(time_scaled(t, pole.im, arena))), arena)),
+                        (Number::from(-2) * coefficient.im, time_factor(t, k, pole.re, Some(Sin(time_scaled(t, pole.im, arena))), arena)),
+                    ]
+                };
+
+                for (coefficient, factor) in parts {
+                    let coefficient = coefficient / Number::factorial(k);
+
+                    if coefficient.is_zero() || (!coefficient.is_exact() && coefficient.abs().to_f64() < 1e-9) {
+                        continue;
+                    }
+
+                    terms.push(match factor {
+                        Constant(c) => Constant(coefficient * c),
+                        factor if coefficient.is_one() => factor,
+                        factor => Scale { coefficient, term: ArenaTerm::new_in(factor, arena) },
+                    });
+                }
+            }
+        }
+
+        Some(terms)
+    }
+
+    fn add(&self, other: &RationalFunction) -> Self {
+        if self.denominator == other.denominator {
+            return RationalFunction {
+                numerator: poly_add(&self.numerator, &other.numerator),
+                denominator: self.denominator.clone(),
+            };
+        }
+
+        RationalFunction {
+            numerator: poly_add(
+                &poly_mul(&self.numerator, &other.denominator),
+                &poly_mul(&other.numerator, &self.denominator),
+            ),
+            denominator: poly_mul(&self.denominator, &other.denominator),
+        }
+    }
+
+    fn mul(&self, other: &RationalFunction) -> Self {
+        RationalFunction {
+            numerator: poly_mul(&self.numerator, &other.numerator),
+            denominator: poly_mul(&self.denominator, &other.denominator),
+        }
+    }
+
+    fn powi(&self, n: i32) -> Option<Self> {
+        let base = match n {
+            _ if n >= 0 => self.clone(),
+            _ if is_zero(&self.numerator) => return None,
+            _ => RationalFunction { numerator: self.denominator.clone(), denominator: self.numerator.clone() },
+        };
+
+        Some((0..n.unsigned_abs()).fold(RationalFunction::constant(Number::from(1)), |acc, _| acc.mul(&base)))
+    }
+
+    /// every distinct pole (only the upper one of each conjugate pair) together with the
+    /// coefficients `A_1..A_m` of `A_k/(s - p)^k` in the expansion. poles `roots_in` finds exactly
+    /// (rational ones, and quadratic ones with a rational discriminant) get exact coefficients,
+    /// as long as the fraction's own coefficients are exact, the others are found numerically.
+    fn partial_fractions(&self, s: Name, arena: &Bump) -> Option<Vec<Expansion>> {
+        let numerator = trim(&self.numerator);
+        let denominator = trim(&self.denominator);
+
+        if is_zero(&numerator) || denominator.len() < 2 {
+            return Some(Vec::new());
+        }
+
+        let coefficients = denominator.iter().map(|&c| Term::Constant(c)).collect();
+        let poles = Polynomial::new_in(s, coefficients, arena).ok()?.roots_in(arena).ok()?;
+
+        let exact = |term: &Term| match term {
+            Term::Constant(c) if c.is_exact() => Some(*c),
+            _ => None,
+        };
+
+        let mut fractions = Vec::with_capacity(poles.len());
+
+        for pole in poles {
+            let multiplicity = pole.multiplicity;
+            let value = Complex::new(pole.re.eval(&|_| None).ok()?, pole.im.eval(&|_| None).ok()?);
+
+            if value.im < 0.0 {
+                continue;
+            }
+
+            let (pole, coefficients) = match (exact(&pole.re), exact(&pole.im)) {
+                (Some(re), Some(im)) => {
+                    let pole = Complex { re, im };
+                    let lift = |p: &[Number]| p.iter().map(|&c| Complex::from(c)).collect::<Vec<_>>();
+
+                    (pole, expansion(&lift(&numerator), &lift(&denominator), pole, multiplicity))
+                },
+                _ => {
+                    let lift = |p: &[Number]| p.iter().map(|c| Complex::from(c.to_f64())).collect::<Vec<_>>();
+                    let coefficients = expansion(&lift(&numerator), &lift(&denominator), value, multiplicity);
+
+                    (
+                        Complex { re: tidy(value.re), im: tidy(value.im) },
+                        coefficients.into_iter().map(|c| Complex { re: tidy(c.re), im: tidy(c.im) }).collect(),
+                    )
+                },
+            };
+
+            fractions.push((pole, coefficients));
+        }
+
+        Some(fractions)
+    }
+}
+
+/// `t^k e^(at) trig`, leaving out the factors that are 1.
+pub(crate) fn time_factor<'arena>(t: Name, k: usize, a: Number, trig: Option<Term<'arena>>, arena: &'arena Bump) -> Term<'arena> {
+    use Term::*;
+
+    let mut factors = Vec::new();
+
+    match k {
+        0 => {},
+        1 => factors.push(Var(t)),
+        _ => factors.push(Power {
+            base: ArenaTerm::new_in(Var(t), arena),
+            exponent: ArenaTerm::new_in(Constant(Number::from(k)), arena),
+        }),
+    }
+
+    if !a.is_zero() {
+        factors.push(Exponential(Number::E, time_scaled(t, a, arena)));
+    }
+
+    factors.extend(trig);
+
+    factors.into_iter()
+        .reduce(|acc, factor| Product(ArenaTerm::new_in(acc, arena), ArenaTerm::new_in(factor, arena)))
+        .unwrap_or(Constant(Number::from(1)))
+}
+
+pub(crate) fn time_scaled<'arena>(t: Name, w: Number, arena: &'arena Bump) -> ArenaTerm<'arena> {
+    match w {
+        _ if w.is_one() => ArenaTerm::new_in(Term::Var(t), arena),
+        _ => ArenaTerm::new_in(Term::Scale { coefficient: w, term: ArenaTerm::new_in(Term::Var(t), arena) }, arena),
+    }
+}
+
+/// `x` if it's exact, otherwise `tidy`'s guess at it.
+fn tidied(x: Number) -> Number {
+    match x {
+        Number::Rational(..) => x,
+        Number::Float(x) => tidy(x),
+    }
+}
+
+/// snaps floating point noise onto nearby simple fractions, which are exact from then on.
+pub(crate) fn tidy(x: f64) -> Number {
+    for d in 1..=12 {
+        let scaled = x * d as f64;
+        if (scaled - scaled.round()).abs() < 1e-9 * d as f64 {
+            return Number::ratio(scaled.round() as i128, d).unwrap_or(Number::Float(x));
+        }
+    }
+
+    Number::Float(x)
+}
+
+/// adds `e^(-as) F(s)` to a sum of delayed fractions.
+fn merge_delayed(mut parts: Vec<(Number, RationalFunction)>, (delay, fraction): (Number, RationalFunction)) -> Vec<(Number, RationalFunction)> {
+    match parts.iter_mut().find(|(like, _)| *like == delay) {
+        Some((_, total)) => *total = total.add(&fraction),
+        None => parts.push((delay, fraction)),
+    }
+
+    parts
+}
+
+fn delayed_product(p: &[(Number, RationalFunction)], q: &[(Number, RationalFunction)]) -> Vec<(Number, RationalFunction)> {
+    p.iter()
+        .flat_map(|(a, f)| q.iter().map(move |(b, g)| (*a + *b, f.mul(g))))
+        .fold(Vec::new(), merge_delayed)
+}
+
+/// `p` without its trailing zeros, counting inexact coefficients that are tiny next to the others as zero.
+fn trim(p: &[Number]) -> Vec<Number> {
+    let largest = p.iter().fold(0.0f64, |m, c| m.max(c.to_f64().abs()));
+    let negligible = |c: &Number| c.is_zero() || (!c.is_exact() && c.to_f64().abs() <= 1e-12 * largest);
+
+    let len = p.iter().rposition(|c| !negligible(c)).map_or(0, |i| i + 1);
+    p[..len].to_vec()
+}
+
+fn is_zero(p: &[Number]) -> bool {
+    p.iter().all(|c| c.is_zero())
+}
+
+fn poly_add(p: &[Number], q: &[Number]) -> Vec<Number> {
+    (0..p.len().max(q.len()))
+        .map(|i| p.get(i).copied().unwrap_or_default() + q.get(i).copied().unwrap_or_default())
+        .collect()
+}
+
+fn poly_mul(p: &[Number], q: &[Number]) -> Vec<Number> {
+    let mut product = vec![Number::from(0); p.len() + q.len() - 1];
+
+    for (i, &a) in p.iter().enumerate() {
+        for (j, &b) in q.iter().enumerate() {
+            product[i + j] += a * b;
+        }
+    }
+
+    product
+}
+
+/// the coefficients `A_1..A_m` of `A_k/(s - p)^k` in `numerator/denominator`, for a pole `p` of multiplicity `m`.
+fn expansion<T>(numerator: &[T], denominator: &[T], pole: T, multiplicity: usize) -> Vec<T>
+where
+    T: Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>,
+{
+    /* the denominator with this pole divided out */
+    let rest = (0..multiplicity).fold(denominator.to_vec(), |rest, _| deflated(&rest, pole));
+
+    /* taylor-expand numerator/rest around the pole, the first m coefficients are A_m..A_1 */
+    let n = taylor(numerator, pole, multiplicity);
+    let d = taylor(&rest, pole, multiplicity);
+
+    let mut g: Vec<T> = Vec::with_capacity(multiplicity);
+    for k in 0..multiplicity {
+        let known = (1..=k).fold(T::default(), |acc, j| acc + d[j] * g[k - j]);
+        g.push((n[k] - known) / d[0]);
+    }
+
+    g.reverse();
+    g
+}
+
+/// `p(x)/(x - root)`, dropping the remainder.
+fn deflated<T: Copy + Default + Add<Output = T> + Mul<Output = T>>(p: &[T], root: T) -> Vec<T> {
+    let mut quotient = vec![T::default(); p.len().saturating_sub(1)];
+    let mut carry = T::default();
+
+    for i in (1..p.len()).rev() {
+        carry = carry * root + p[i];
+        quotient[i - 1] = carry;
+    }
+
+    quotient
+}
+
+/// first `count` taylor coefficients of `p` around `at`, by repeated synthetic division.
+pub(crate) fn taylor<T: Copy + Default + Add<Output = T> + Mul<Output = T>>(p: &[T], at: T, count: usize) -> Vec<T> {
+    let mut p = p.to_vec();
+    let mut coefficients = Vec::with_capacity(count);
+
+    for _ in 0..count {
+        let mut remainder = T::default();
+        let mut quotient = vec![T::default(); p.len().saturating_sub(1)];
+
+        for i in (0..p.len()).rev() {
+            remainder = remainder * at + p[i];
+            if i > 0 {
+                quotient[i - 1] = remainder;
+            }
+        }
+
+        coefficients.push(remainder);
+        p = quotient;
+    }
+
+    coefficients
+}
@@ -0,0 +1,346 @@
+use crate::{Term, ArenaTerm, LadesError, Number, Name};
+use crate::simplify::checked_ln;
+use bumpalo::Bump;
+
+impl<'arena> Term<'arena> {
+    pub fn laplace_in(self, wrt_time: Term, s_var: Term, arena: &'arena Bump) -> Result<ArenaTerm<'arena>, LadesError> {
+        use Term::*;
+
+        match (wrt_time, s_var) {
+            (Var(t), Var(s)) => self.simplify_in(arena)?.transform(t, s, arena)?.simplify_in(arena),
+            (Var(_), s) => Err(LadesError::NonVariable(s.to_string())),
+            (t, _) => Err(LadesError::NonVariable(t.to_string())),
+        }
+    }
+
+    fn transform(&self, t: Name, s: Name, arena: &'arena Bump) -> Result<ArenaTerm<'arena>, LadesError> {
+        use Term::*;
+
+        let unsupported = || LadesError::Unsupported(self.to_string());
+
+        /* L{c} = c/s */
+        if !self.contains_var(t) {
+            return Ok(match self {
+                Constant(c) => over_s_power(Constant(*c), s, 1, arena),
+                _ => over_s_power(self.clone(), s, 1, arena),
+            });
+        }
+
+        match self {
+            /* L{t^n} = n!/s^(n+1) */
+            Var(_) | Power { .. } => {
+                let n = self.monomial_degree().ok_or_else(unsupported)?;
+
+                Ok(over_s_power(Constant(Number::factorial(n)), s, n + 1, arena))
+            },
+
+            /* L{b^(at + c)} = b^c / (s - a ln(b)) */
+            Exponential(base, exponent) => {
+                let (slope, intercept) = exponent.linear_coefficients(t).ok_or_else(unsupported)?;
+
+                Ok(ArenaTerm::new_in(Quotient(
+                    ArenaTerm::new_in(Constant(base.pow(intercept)), arena),
+                    linear_in_s(s, Number::from(1), -slope * checked_ln(*base)?, arena),
+                ), arena))
+            },
+
+            /* L{sin(wt + p)} = (sin(p)s + cos(p)w) / (s^2 + w^2) */
+            Sin(term) => {
+                let (w, p) = term.linear_coefficients(t).ok_or_else(unsupported)?;
+                Ok(over_s_squared_plus(linear_in_s(s, p.sin(), p.cos() * w, arena), s, w, arena))
+            },
+
+            /* L{cos(wt + p)} = (cos(p)s - sin(p)w) / (s^2 + w^2) */
+            Cos(term) => {
+                let (w, p) = term.linear_coefficients(t).ok_or_else(unsupported)?;
+                Ok(over_s_squared_plus(linear_in_s(s, p.cos(), -p.sin() * w, arena), s, w, arena))
+            },
+
+            Scale { coefficient, term } => Ok(ArenaTerm::new_in(Scale {
+                coefficient: *coefficient,
+                term: term.transform(t, s, arena)?,
+            }, arena)),
+
+            Sum(t1, t2) => Ok(ArenaTerm::new_in(Sum(
+                t1.transform(t, s, arena)?,
+                t2.transform(t, s, arena)?,
+            ), arena)),
+
+            Negation(_) | Quotient(..) | Reciprocal(_) => self.clone().lowered(arena).transform(t, s, arena),
+
+            Heaviside(_) | Dirac(_) => self.transform_switched(Constant(Number::from(1)), t, s, arena),
+
+            /* f + u(switch)(g - f) */
+            Piecewise { switch, below, above } => transform_piecewise(switch, below, above, Constant(Number::from(1)), t, s, arena),
+
+            Product(..) => {
+                let mut factors = Vec::new();
+                self.collect_factors(&mut factors);
+
+                /* steps and impulses shift whatever they multiply */
+                if let Some(i) = factors.iter().position(|factor| matches!(factor, Heaviside(_) | Dirac(_) | Piecewise { .. }) && factor.contains_var(t)) {
+                    let switched = factors.remove(i);
+                    let rest = factors.into_iter()
+                        .reduce(|product, factor| Product(ArenaTerm::new_in(product, arena), ArenaTerm::new_in(factor, arena)))
+                        .unwrap_or(Constant(Number::from(1)));
+
+                    return match switched {
+                        Piecewise { switch, below, above } => transform_piecewise(&switch, &below, &above, rest, t, s, arena),
+                        _ => switched.transform_switched(rest, t, s, arena),
+                    };
+                }
+
+                let mut gain = Number::from(1);
+                let mut constants = Vec::new();
+                let mut shift = Number::from(0);
+                let mut t_power = 0;
+                let mut rest = None;
+
+                for factor in factors {
+                    if let Constant(c) = factor {
+                        gain *= c;
+                    } else if !factor.contains_var(t) {
+                        constants.push(factor);
+                    } else if let Some(n) = factor.monomial_degree() {
+                        t_power += n;
+                    } else if let Exponential(base, exponent) = &factor {
+                        let (slope, intercept) = exponent.linear_coefficients(t).ok_or_else(unsupported)?;
+                        gain *= base.pow(intercept);
+                        shift += slope * checked_ln(*base)?;
+                    } else if rest.is_none() {
+                        rest = Some(factor);
+                    } else {
+                        return Err(unsupported());
+                    }
+                }
+
+                /* L{t^n} is known outright, where differentiating 1/s n times would leave a quotient to cancel */
+                let mut transformed = match rest {
+                    Some(rest) => rest.transform(t, s, arena)?,
+                    None => {
+                        let n = std::mem::take(&mut t_power);
+                        over_s_power(Constant(Number::factorial(n)), s, n + 1, arena)
+                    },
+                };
+
+                /* L{t^n f(t)} = (-1)^n F^(n)(s) */
+                for _ in 0..t_power {
+                    transformed = ArenaTerm::new_in(Scale {
+                        coefficient: Number::from(-1),
+                        term: (*transformed).differentiate_in(Var(s), arena)?,
+                    }, arena);
+                }
+
+                /* L{e^(at) f(t)} = F(s - a) */
+                if !shift.is_zero() {
+                    transformed = transformed.substitute_in(s, &Sum(
+                        ArenaTerm::new_in(Var(s), arena),
+                        ArenaTerm::new_in(Constant(-shift), arena),
+                    ), arena);
+                }
+
+                for constant in constants {
+                    transformed = ArenaTerm::new_in(Product(ArenaTerm::new_in(constant, arena), transformed), arena);
+                }
+
+                Ok(ArenaTerm::new_in(Scale { coefficient: gain, term: transformed }, arena))
+            },
+
+            _ => Err(unsupported()),
+        }
+    }
+
+    /// `L{u(ct - ca) rest}` or `L{δ(ct - ca) rest}` for the step or impulse `self`, by the second shifting
+    /// theorem `L{u(t - a) f(t - a)} = e^(-as) F(s)`.
+    fn transform_switched(&self, rest: Term<'arena>, t: Name, s: Name, arena: &'arena Bump) -> Result<ArenaTerm<'arena>, LadesError> {
+        use Term::*;
+
+        let unsupported = || LadesError::Unsupported(self.to_string());
+
+        let (Heaviside(argument) | Dirac(argument)) = self else {
+            return Err(unsupported());
+        };
+
+        let (c, intercept) = argument.linear_coefficients(t).filter(|(c, _)| !c.is_zero()).ok_or_else(unsupported)?;
+        let a = -intercept / c;
+
+        let delayed = |transformed: ArenaTerm<'arena>| match a {
+            _ if a.is_zero() => transformed,
+            _ => ArenaTerm::new_in(Product(
+                ArenaTerm::new_in(Exponential(Number::E, ArenaTerm::new_in(Scale { coefficient: -a, term: ArenaTerm::new_in(Var(s), arena) }, arena)), arena),
+                transformed,
+            ), arena),
+        };
+
+        /* δ(c(t - a)) = δ(t - a)/|c| picks out f(a), if a is where the transform integrates */
+        if let Dirac(_) = self {
+            return Ok(match a {
+                _ if a.is_negative() => ArenaTerm::new_in(Constant(Number::from(0)), arena),
+                _ => ArenaTerm::new_in(Scale {
+                    coefficient: Number::from(1) / c.abs(),
+                    term: delayed(rest.substitute_in(t, &Constant(a), arena)),
+                }, arena),
+            });
+        }
+
+        /* for c < 0 the step is 1 up to a instead, so it's 1 - u(t - a) */
+        match (c.is_positive(), a.is_positive()) {
+            (true, false) => rest.transform(t, s, arena),
+            (false, false) => Ok(ArenaTerm::new_in(Constant(Number::from(0)), arena)),
+
+            (after, true) => {
+                let shifted = rest.substitute_in(t, &Sum(ArenaTerm::new_in(Var(t), arena), ArenaTerm::new_in(Constant(a), arena)), arena);
+                let late = delayed(shifted.transform(t, s, arena)?);
+
+                Ok(match after {
+                    true => late,
+                    false => ArenaTerm::new_in(Sum(rest.transform(t, s, arena)?, ArenaTerm::new_in(Scale { coefficient: Number::from(-1), term: late }, arena)), arena),
+                })
+            },
+        }
+    }
+
+    /// splits nested products (and the coefficients of scales inside them) into a flat list of factors.
+    fn collect_factors(&self, factors: &mut Vec<Term<'arena>>) {
+        use Term::*;
+
+        match self {
+            Product(t1, t2) => {
+                t1.collect_factors(factors);
+                t2.collect_factors(factors);
+            },
+
+            Scale { coefficient, term } => {
+                factors.push(Constant(*coefficient));
+                term.collect_factors(factors);
+            },
+
+            Negation(term) => {
+                factors.push(Constant(Number::from(-1)));
+                term.collect_factors(factors);
+            },
+
+            _ => factors.push(self.clone()),
+        }
+    }
+
+    /// `n` if the term is `t^n` for a non-negative integer `n`. only meant for terms known to contain `t`.
+    fn monomial_degree(&self) -> Option<usize> {
+        use Term::*;
+
+        match self {
+            Var(_) => Some(1),
+            Power { base, exponent } => match (&**base, &**exponent) {
+                (Var(_), Constant(n)) => n.to_integer().and_then(|n| usize::try_from(n).ok()),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// `(a, b)` such that the term equals `a*t + b`, if it's linear in `t` with numeric coefficients. `π` and `e`
+    /// don't count as numeric, so they never turn into floats here.
+    pub(crate) fn linear_coefficients(&self, t: Name) -> Option<(Number, Number)> {
+        use Term::*;
+
+        let zero = Number::from(0);
+
+        match self {
+            Constant(c) => Some((zero, *c)),
+            Var(v) if *v == t => Some((Number::from(1), zero)),
+
+            Sum(t1, t2) => {
+                let (a1, b1) = t1.linear_coefficients(t)?;
+                let (a2, b2) = t2.linear_coefficients(t)?;
+                Some((a1 + a2, b1 + b2))
+            },
+
+            Scale { coefficient, term } => {
+                let (a, b) = term.linear_coefficients(t)?;
+                Some((*coefficient * a, *coefficient * b))
+            },
+
+            Negation(term) => {
+                let (a, b) = term.linear_coefficients(t)?;
+                Some((-a, -b))
+            },
+
+            Quotient(term, denominator) => match **denominator {
+                Constant(c) if !c.is_zero() => {
+                    let (a, b) = term.linear_coefficients(t)?;
+                    Some((a / c, b / c))
+                },
+                _ => None,
+            },
+
+            Product(t1, t2) => match (t1.linear_coefficients(t)?, t2.linear_coefficients(t)?) {
+                ((zero, c), (a, b)) | ((a, b), (zero, c)) if zero.is_zero() => Some((c * a, c * b)),
+                _ => None,
+            },
+
+            _ => None,
+        }
+    }
+}
+
+/// `L{piecewise(switch, f, g) rest}`, written as `L{f rest} + L{u(switch) (g - f) rest}`.
+fn transform_piecewise<'arena>(
+    switch: &ArenaTerm<'arena>,
+    below: &ArenaTerm<'arena>,
+    above: &ArenaTerm<'arena>,
+    rest: Term<'arena>,
+    t: Name,
+    s: Name,
+    arena: &'arena Bump
+) -> Result<ArenaTerm<'arena>, LadesError> {
+    use Term::*;
+
+    let rest = ArenaTerm::new_in(rest, arena);
+    let jump = Sum(above.clone(), ArenaTerm::new_in(Negation(below.clone()), arena));
+
+    Ok(ArenaTerm::new_in(Sum(
+        Product(below.clone(), rest.clone()).transform(t, s, arena)?,
+        Heaviside(switch.clone()).transform_switched(Product(ArenaTerm::new_in(jump, arena), rest), t, s, arena)?,
+    ), arena))
+}
+
+fn s_power<'arena>(s: Name, exponent: Number, arena: &'arena Bump) -> ArenaTerm<'arena> {
+    ArenaTerm::new_in(match exponent {
+        _ if exponent.is_one() => Term::Var(s),
+        _ => Term::Power {
+            base: ArenaTerm::new_in(Term::Var(s), arena),
+            exponent: ArenaTerm::new_in(Term::Constant(exponent), arena),
+        },
+    }, arena)
+}
+
+/// `numerator / s^n`
+fn over_s_power<'arena>(numerator: Term<'arena>, s: Name, n: usize, arena: &'arena Bump) -> ArenaTerm<'arena> {
+    ArenaTerm::new_in(Term::Quotient(ArenaTerm::new_in(numerator, arena), s_power(s, Number::from(n), arena)), arena)
+}
+
+/// `slope*s + intercept`, leaving out whichever part vanishes.
+fn linear_in_s<'arena>(s: Name, slope: Number, intercept: Number, arena: &'arena Bump) -> ArenaTerm<'arena> {
+    use Term::*;
+
+    let s_term = match slope {
+        _ if slope.is_one() => Var(s),
+        _ => Scale { coefficient: slope, term: ArenaTerm::new_in(Var(s), arena) },
+    };
+
+    ArenaTerm::new_in(match (slope, intercept) {
+        _ if slope.is_zero() => Constant(intercept),
+        _ if intercept.is_zero() => s_term,
+        _ => Sum(ArenaTerm::new_in(s_term, arena), ArenaTerm::new_in(Constant(intercept), arena)),
+    }, arena)
+}
+
+/// `numerator / (s^2 + w^2)`
+fn over_s_squared_plus<'arena>(numerator: ArenaTerm<'arena>, s: Name, w: Number, arena: &'arena Bump) -> ArenaTerm<'arena> {
+    use Term::*;
+
+    ArenaTerm::new_in(Quotient(
+        numerator,
+        ArenaTerm::new_in(Sum(s_power(s, Number::from(2), arena), ArenaTerm::new_in(Constant(w * w), arena)), arena),
+    ), arena)
+}
@@ -1,33 +1,108 @@
 #![feature(allocator_api)]
 
+mod error;
+mod number;
+mod name;
 mod display;
 mod simplify;
+mod rewrite;
+mod trace;
+mod pattern;
+mod trigonometry;
+mod canonical;
+mod polynomial;
+mod roots;
 mod differentiate;
+mod integrate;
+mod laplace;
+mod inverse_laplace;
+mod complex;
+mod equation;
+mod undetermined;
+mod variation;
+mod parse;
+mod builder;
+mod eval;
 mod tests;
 
 use bumpalo::Bump;
 
-/*struct Equation<'a> {
-    pub lhs: Vec<Term<'a>>,
-    pub rhs: Vec<Term<'a>>
-}*/
+pub use error::LadesError;
+pub use number::Number;
+pub use name::Name;
+pub use equation::{Equation, System};
+pub use polynomial::Polynomial;
+pub use roots::Root;
+pub use parse::{ParseError, ParseErrorKind};
+pub use builder::{Context, Expr, IntoExpr};
+pub use eval::EvalError;
+pub use rewrite::{Rewrite, Rules, SimplifyObserver, PrintObserver};
+pub use trace::{SimplifyTrace, Step};
+pub use pattern::{Pattern, Bindings};
 
-type ArenaTerm<'arena> = Box<Term<'arena>, &'arena Bump>;
+pub type ArenaTerm<'arena> = Box<Term<'arena>, &'arena Bump>;
 
 #[derive(Clone, PartialEq)]
-enum Term<'arena> {
-    Constant(f64), // for now, arbitrary symbolic constants later.
-    Var(char),
+pub enum Term<'arena> {
+    Constant(Number),
+    Var(Name),
+    /// a named parameter, constant as far as differentiation and the transforms are concerned.
+    Symbol(&'arena str),
+    Pi,
+    E,
+    /// the imaginary unit, `i^2 = -1`.
+    I,
     Sum(ArenaTerm<'arena>, ArenaTerm<'arena>),
     
-    Scale { coefficient: f64, term: ArenaTerm<'arena> },
+    Scale { coefficient: Number, term: ArenaTerm<'arena> },
     Product(ArenaTerm<'arena>, ArenaTerm<'arena>),
+    /// `numerator/denominator`
+    Quotient(ArenaTerm<'arena>, ArenaTerm<'arena>),
+    /// `-term`, which also makes `a - b` a `Sum` of `a` and `-b`.
+    Negation(ArenaTerm<'arena>),
+    /// `1/term`
+    Reciprocal(ArenaTerm<'arena>),
     
     Power { base: ArenaTerm<'arena>, exponent: ArenaTerm<'arena> },
-    Exponential(f64, ArenaTerm<'arena>),
+    /// `base^term` for a number `base`. `Number::E` stands for the symbolic `e` here, which never folds.
+    Exponential(Number, ArenaTerm<'arena>),
     
     Sin(ArenaTerm<'arena>),
     Cos(ArenaTerm<'arena>),
+    Ln(ArenaTerm<'arena>),
+
+    /// the unit step: 0 where the term is negative, 1 from where it's 0 on.
+    Heaviside(ArenaTerm<'arena>),
+    /// the unit impulse at the term's root.
+    Dirac(ArenaTerm<'arena>),
+    /// `below` where `switch` is negative, `above` from where it's 0 on.
+    Piecewise { switch: ArenaTerm<'arena>, below: ArenaTerm<'arena>, above: ArenaTerm<'arena> },
 
     Derivative { order: usize, wrt: ArenaTerm<'arena>, term: ArenaTerm<'arena> },
+    /// an antiderivative of `term` the integrator couldn't find in closed form.
+    Integral { wrt: ArenaTerm<'arena>, term: ArenaTerm<'arena> },
+}
+
+impl Term<'_> {
+    /// the single-letter variable `c`, or an error if it isn't an ascii letter or it's `e` or `i`.
+    pub fn var(c: char) -> Result<Self, ParseError> {
+        Name::letter(c).map(Term::Var)
+    }
+
+    pub fn contains_var(&self, var: Name) -> bool {
+        use Term::*;
+
+        match self {
+            Constant(_) | Symbol(_) | Pi | E | I => false,
+            Var(c) => *c == var,
+
+            Sum(t1, t2) | Product(t1, t2) | Quotient(t1, t2) => t1.contains_var(var) || t2.contains_var(var),
+            Negation(term) | Reciprocal(term) => term.contains_var(var),
+            Power { base, exponent } => base.contains_var(var) || exponent.contains_var(var),
+            Scale { term, .. } | Exponential(_, term) | Sin(term) | Cos(term) | Ln(term) => term.contains_var(var),
+            Heaviside(term) | Dirac(term) => term.contains_var(var),
+            Piecewise { switch, below, above } => switch.contains_var(var) || below.contains_var(var) || above.contains_var(var),
+            Derivative { wrt, term, .. } | Integral { wrt, term } => wrt.contains_var(var) || term.contains_var(var),
+        }
+    }
 }
@@ -0,0 +1,128 @@
+use crate::{ParseError, ParseErrorKind};
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+use std::sync::{LazyLock, Mutex};
+use std::{fmt, ptr};
+
+/// spelled out greek letters, which read as one variable rather than a product of letters.
+/// `pi` isn't one of them, that's `π`.
+const GREEK: [&str; 23] = [
+    "alpha", "beta", "gamma", "delta", "epsilon", "zeta", "eta", "theta", "iota", "kappa", "lambda", "mu",
+    "nu", "xi", "omicron", "rho", "sigma", "tau", "upsilon", "phi", "chi", "psi", "omega",
+];
+
+/// the names the parser reads as `E` and `I`, which can't be variables or they wouldn't parse back.
+const CONSTANTS: [&str; 2] = ["e", "i"];
+
+/// every single-letter name, so the most common names are never allocated.
+const LETTERS: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// every longer name that was ever spelled, each stored once. names outlive any one arena (the same
+/// `t` shows up in a term and in its transform), so the table is shared and never shrinks.
+static SYMBOLS: LazyLock<Mutex<HashSet<&'static str>>> = LazyLock::new(Default::default);
+
+/// a variable's name: an ascii letter or a spelled out greek letter, optionally followed by an index,
+/// like `x`, `theta`, `x_1`, `i_L` or `v_out`. names are interned, so comparing or hashing one only
+/// looks at where its spelling is stored. they're still ordered alphabetically.
+#[derive(Clone, Copy, Debug)]
+pub struct Name(&'static str);
+
+impl Name {
+    /// the single letter `c`, or an error if it isn't an ascii letter or it's `e` or `i`.
+    pub fn letter(c: char) -> Result<Self, ParseError> {
+        match LETTERS.find(c) {
+            Some(i) if CONSTANTS.contains(&&LETTERS[i..i + 1]) => Err(ParseError { kind: ParseErrorKind::ReservedName(c.to_string()), span: 0..1 }),
+            Some(i) if c.is_ascii() => Ok(Name(&LETTERS[i..i + 1])),
+            _ => Err(ParseError { kind: ParseErrorKind::UnexpectedChar(c), span: 0..c.len_utf8() }),
+        }
+    }
+
+    /// the name spelled `name`, or an error pointing at where it stops following the naming rules.
+    pub fn new(name: &str) -> Result<Self, ParseError> {
+        let length = name_length(name);
+
+        match name[length..].chars().next() {
+            None if CONSTANTS.contains(&name) => Err(ParseError { kind: ParseErrorKind::ReservedName(name.to_string()), span: 0..length }),
+            None if length > 0 => Ok(Name::intern(name)),
+            None => Err(ParseError { kind: ParseErrorKind::UnexpectedEnd, span: 0..0 }),
+            Some(c) => Err(ParseError { kind: ParseErrorKind::UnexpectedChar(c), span: length..length + c.len_utf8() }),
+        }
+    }
+
+    /// a name already known to follow the rules. single letters come out of `LETTERS`, anything
+    /// else out of `SYMBOLS`, where it's added the first time it's spelled.
+    pub(crate) fn intern(name: &str) -> Self {
+        if let Some(i) = LETTERS.find(name).filter(|_| name.len() == 1) {
+            return Name(&LETTERS[i..i + 1]);
+        }
+
+        let mut symbols = SYMBOLS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        match symbols.get(name) {
+            Some(interned) => Name(interned),
+            None => {
+                let interned: &'static str = Box::leak(name.into());
+                symbols.insert(interned);
+                Name(interned)
+            },
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        self.0
+    }
+}
+
+impl PartialEq for Name {
+    fn eq(&self, other: &Self) -> bool {
+        ptr::eq(self.0, other.0)
+    }
+}
+
+impl Eq for Name {}
+
+impl Hash for Name {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        ptr::hash(self.0, state)
+    }
+}
+
+impl PartialOrd for Name {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Name {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.cmp(other.0)
+    }
+}
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// the length of the name `s` starts with, or 0. the parser and `Display` both go by this.
+pub(crate) fn name_length(s: &str) -> usize {
+    let letters = GREEK.iter()
+        .filter(|greek| s.starts_with(*greek))
+        .map(|greek| greek.len())
+        .max()
+        .or_else(|| s.starts_with(|c: char| c.is_ascii_alphabetic()).then_some(1));
+
+    let Some(letters) = letters else {
+        return 0;
+    };
+
+    /* `_` only belongs to the name if an index follows it */
+    match s[letters..].strip_prefix('_') {
+        Some(rest) => match rest.find(|c: char| !c.is_ascii_alphanumeric()).unwrap_or(rest.len()) {
+            0 => letters,
+            index => letters + 1 + index,
+        },
+        None => letters,
+    }
+}
@@ -0,0 +1,392 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Sub, Mul, Div, Neg, AddAssign, MulAssign};
+use std::str::FromStr;
+
+/// a constant or coefficient. exact as long as it's a ratio of integers that fits in an `i128`, so
+/// `1/3 + 1/3 + 1/3` is exactly 1.
+///
+/// promotion: combining two exact numbers stays exact, unless the result overflows or isn't rational
+/// (`2^(1/2)`, `ln(3)`, ...). anything combined with a float is a float.
+#[derive(Clone, Copy, Debug)]
+pub enum Number {
+    /// `numerator/denominator` in lowest terms, with a positive denominator.
+    Rational(i128, i128),
+    /// an explicitly inexact value.
+    Float(f64),
+}
+
+impl Number {
+    /// euler's number, as the base of `Exponential`s. there's no exact value to have.
+    pub const E: Number = Number::Float(std::f64::consts::E);
+
+    /// `numerator/denominator`, reduced. `None` if the denominator is 0.
+    pub fn ratio(numerator: i128, denominator: i128) -> Option<Self> {
+        if denominator == 0 {
+            return None;
+        }
+
+        let divisor = gcd(numerator, denominator);
+        let (numerator, denominator) = (numerator / divisor, denominator / divisor);
+
+        Some(match denominator {
+            _ if denominator < 0 => match (numerator.checked_neg(), denominator.checked_neg()) {
+                (Some(n), Some(d)) => Number::Rational(n, d),
+                _ => Number::Float(numerator as f64 / denominator as f64),
+            },
+            _ => Number::Rational(numerator, denominator),
+        })
+    }
+
+    pub fn to_f64(self) -> f64 {
+        match self {
+            Number::Rational(n, d) => n as f64 / d as f64,
+            Number::Float(x) => x,
+        }
+    }
+
+    pub fn is_exact(self) -> bool {
+        matches!(self, Number::Rational(..))
+    }
+
+    pub fn is_zero(self) -> bool {
+        match self {
+            Number::Rational(n, _) => n == 0,
+            Number::Float(x) => x == 0.0,
+        }
+    }
+
+    pub fn is_one(self) -> bool {
+        match self {
+            Number::Rational(n, d) => n == 1 && d == 1,
+            Number::Float(x) => x == 1.0,
+        }
+    }
+
+    pub fn is_negative(self) -> bool {
+        self.to_f64() < 0.0
+    }
+
+    pub fn is_positive(self) -> bool {
+        self.to_f64() > 0.0
+    }
+
+    /// whether it's written as `n/d`, i.e. it's exact and neither whole nor a terminating decimal.
+    pub fn is_fraction(self) -> bool {
+        match self {
+            Number::Rational(n, d) => d != 1 && decimal_digits(n, d).is_none(),
+            Number::Float(_) => false,
+        }
+    }
+
+    /// the value, if it's a whole number (exact or not).
+    pub fn to_integer(self) -> Option<i128> {
+        match self {
+            Number::Rational(n, 1) => Some(n),
+            Number::Rational(..) => None,
+            Number::Float(x) if x.fract() == 0.0 && x.abs() < i128::MAX as f64 => Some(x as i128),
+            Number::Float(_) => None,
+        }
+    }
+
+    pub fn abs(self) -> Self {
+        match self.is_negative() {
+            true => -self,
+            false => self,
+        }
+    }
+
+    /// `self^exponent`, exact for exact bases and integer exponents, and for exact `m`th roots like
+    /// `(4/9)^(1/2)` when the exponent is `k/m`. doesn't check the domain.
+    pub fn pow(self, exponent: Number) -> Self {
+        let exact = match (self, exponent) {
+            (Number::Rational(n, d), Number::Rational(k, 1)) if k.unsigned_abs() <= u32::MAX as u128 => {
+                let k32 = k.unsigned_abs() as u32;
+
+                match (n.checked_pow(k32), d.checked_pow(k32)) {
+                    (Some(n), Some(d)) if k >= 0 => Number::ratio(n, d),
+                    (Some(n), Some(d)) => Number::ratio(d, n),
+                    _ => None,
+                }
+            },
+            (Number::Rational(n, d), Number::Rational(k, m)) => u32::try_from(m).ok()
+                .and_then(|m| Some(Number::Rational(exact_root(n, m)?, exact_root(d, m)?)))
+                .map(|root| root.pow(Number::Rational(k, 1)))
+                .filter(|power| power.is_exact()),
+            _ => None,
+        };
+
+        exact.unwrap_or_else(|| Number::Float(self.to_f64().powf(exponent.to_f64())))
+    }
+
+    /// exact for squares of exact numbers. doesn't check the domain.
+    pub fn sqrt(self) -> Self {
+        let exact_root = |n: i128| {
+            let root = (n as f64).sqrt().round() as i128;
+            (root.checked_mul(root) == Some(n)).then_some(root)
+        };
+
+        match self {
+            Number::Rational(n, d) if n >= 0 => match (exact_root(n), exact_root(d)) {
+                (Some(n), Some(d)) => Number::Rational(n, d),
+                _ => Number::Float(self.to_f64().sqrt()),
+            },
+            _ => Number::Float(self.to_f64().sqrt()),
+        }
+    }
+
+    /* the functions only have rational values at rational points in these cases */
+
+    pub fn sin(self) -> Self {
+        match self {
+            Number::Rational(0, _) => self,
+            _ => Number::Float(self.to_f64().sin()),
+        }
+    }
+
+    pub fn cos(self) -> Self {
+        match self {
+            Number::Rational(0, _) => Number::from(1),
+            _ => Number::Float(self.to_f64().cos()),
+        }
+    }
+
+    /// doesn't check the domain either.
+    pub fn ln(self) -> Self {
+        match self {
+            Number::Rational(1, 1) => Number::from(0),
+            _ if self == Number::E => Number::from(1),
+            _ => Number::Float(self.to_f64().ln()),
+        }
+    }
+
+    /// `n!`, exact until it overflows.
+    pub(crate) fn factorial(n: usize) -> Self {
+        (1..=n).map(Number::from).fold(Number::from(1), |product, k| product * k)
+    }
+
+    /// a total order, for sorting terms: by value, with exact numbers before equal floats.
+    pub fn total_cmp(&self, other: &Number) -> Ordering {
+        self.partial_cmp(other)
+            .unwrap_or_else(|| self.to_f64().total_cmp(&other.to_f64()))
+            .then_with(|| other.is_exact().cmp(&self.is_exact()))
+    }
+
+    /// `self op other`, exactly if both are exact and `exact` doesn't overflow.
+    fn combine(
+        self,
+        other: Number,
+        exact: impl Fn(i128, i128, i128, i128) -> Option<(i128, i128)>,
+        float: impl Fn(f64, f64) -> f64,
+    ) -> Number {
+        if let (Number::Rational(n1, d1), Number::Rational(n2, d2)) = (self, other)
+            && let Some(number) = exact(n1, d1, n2, d2).and_then(|(n, d)| Number::ratio(n, d))
+        {
+            return number;
+        }
+
+        Number::Float(float(self.to_f64(), other.to_f64()))
+    }
+}
+
+/// how many decimals `1/d` takes, if it terminates: `max(a, b)` for `d = 2^a 5^b`.
+fn decimal_places(d: i128) -> Option<u32> {
+    let (mut rest, mut twos, mut fives) = (d, 0, 0);
+
+    while rest % 2 == 0 {
+        rest /= 2;
+        twos += 1;
+    }
+
+    while rest % 5 == 0 {
+        rest /= 5;
+        fives += 1;
+    }
+
+    (rest == 1).then_some(twos.max(fives))
+}
+
+/// the `m`th root of `n`, if it's an integer.
+fn exact_root(n: i128, m: u32) -> Option<i128> {
+    let root = match n.is_negative() && m % 2 == 1 {
+        true => -(-n as f64).powf(1.0 / m as f64).round(),
+        false => (n as f64).powf(1.0 / m as f64).round(),
+    };
+
+    let root = root as i128;
+    (root.checked_pow(m) == Some(n)).then_some(root)
+}
+
+/// `n/d` as its decimal places and all of its digits, if it terminates and they fit in an `i128`.
+fn decimal_digits(n: i128, d: i128) -> Option<(u32, i128)> {
+    let places = decimal_places(d)?;
+    Some((places, n.checked_mul(10i128.checked_pow(places)? / d)?))
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+
+    i128::try_from(a).unwrap_or(1).max(1)
+}
+
+/// an exact 0.
+impl Default for Number {
+    fn default() -> Self {
+        Number::from(0)
+    }
+}
+
+impl From<i32> for Number {
+    fn from(n: i32) -> Self {
+        Number::Rational(n as i128, 1)
+    }
+}
+
+impl From<i64> for Number {
+    fn from(n: i64) -> Self {
+        Number::Rational(n as i128, 1)
+    }
+}
+
+impl From<usize> for Number {
+    fn from(n: usize) -> Self {
+        Number::Rational(n as i128, 1)
+    }
+}
+
+impl From<f64> for Number {
+    fn from(x: f64) -> Self {
+        Number::Float(x)
+    }
+}
+
+/// decimal literals are exact, unless they have too many digits for an `i128`.
+impl FromStr for Number {
+    type Err = std::num::ParseFloatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let float: f64 = s.parse()?;
+
+        let (whole, fraction) = s.split_once('.').unwrap_or((s, ""));
+        let digits = format!("{whole}{fraction}");
+
+        let exact = u32::try_from(fraction.len()).ok()
+            .and_then(|places| 10i128.checked_pow(places))
+            .zip(digits.parse::<i128>().ok())
+            .and_then(|(denominator, numerator)| Number::ratio(numerator, denominator));
+
+        Ok(exact.unwrap_or(Number::Float(float)))
+    }
+}
+
+/// exact numbers compare exactly, anything else by value.
+impl PartialEq for Number {
+    fn eq(&self, other: &Number) -> bool {
+        match (self, other) {
+            (Number::Rational(n1, d1), Number::Rational(n2, d2)) => n1 == n2 && d1 == d2,
+            _ => self.to_f64() == other.to_f64(),
+        }
+    }
+}
+
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Number) -> Option<Ordering> {
+        match (self, other) {
+            (Number::Rational(n1, d1), Number::Rational(n2, d2)) => match (n1.checked_mul(*d2), n2.checked_mul(*d1)) {
+                (Some(a), Some(b)) => Some(a.cmp(&b)),
+                _ => self.to_f64().partial_cmp(&other.to_f64()),
+            },
+            _ => self.to_f64().partial_cmp(&other.to_f64()),
+        }
+    }
+}
+
+impl Add for Number {
+    type Output = Number;
+
+    fn add(self, other: Number) -> Number {
+        self.combine(other, |n1, d1, n2, d2| {
+            Some((n1.checked_mul(d2)?.checked_add(n2.checked_mul(d1)?)?, d1.checked_mul(d2)?))
+        }, |a, b| a + b)
+    }
+}
+
+impl Sub for Number {
+    type Output = Number;
+
+    fn sub(self, other: Number) -> Number {
+        self + -other
+    }
+}
+
+impl Mul for Number {
+    type Output = Number;
+
+    fn mul(self, other: Number) -> Number {
+        /* cross-reduce first, so products of big coefficients overflow later */
+        self.combine(other, |n1, d1, n2, d2| {
+            let (g1, g2) = (gcd(n1, d2), gcd(n2, d1));
+            Some(((n1 / g1).checked_mul(n2 / g2)?, (d1 / g2).checked_mul(d2 / g1)?))
+        }, |a, b| a * b)
+    }
+}
+
+/// dividing by an exact 0 gives an infinite float, like dividing by a float 0 does.
+impl Div for Number {
+    type Output = Number;
+
+    fn div(self, other: Number) -> Number {
+        self.combine(other, |n1, d1, n2, d2| Some((n1.checked_mul(d2)?, d1.checked_mul(n2)?)), |a, b| a / b)
+    }
+}
+
+impl Neg for Number {
+    type Output = Number;
+
+    fn neg(self) -> Number {
+        match self {
+            Number::Rational(n, d) => n.checked_neg().map_or(Number::Float(-(n as f64) / d as f64), |n| Number::Rational(n, d)),
+            Number::Float(x) => Number::Float(-x),
+        }
+    }
+}
+
+impl AddAssign for Number {
+    fn add_assign(&mut self, other: Number) {
+        *self = *self + other;
+    }
+}
+
+impl MulAssign for Number {
+    fn mul_assign(&mut self, other: Number) {
+        *self = *self * other;
+    }
+}
+
+/// integers as integers, fractions with a terminating decimal expansion in decimal (`0.75`), others as `n/d`.
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let &Number::Rational(n, d) = self else {
+            return write!(f, "{}", self.to_f64());
+        };
+
+        if d == 1 {
+            return write!(f, "{n}");
+        }
+
+        match decimal_digits(n, d) {
+            Some((places, digits)) => {
+                let sign = if digits < 0 { "-" } else { "" };
+                let digits = format!("{:0>width$}", digits.unsigned_abs(), width = places as usize + 1);
+                let (whole, fraction) = digits.split_at(digits.len() - places as usize);
+
+                write!(f, "{sign}{whole}.{fraction}")
+            },
+            None => write!(f, "{n}/{d}"),
+        }
+    }
+}
@@ -0,0 +1,454 @@
+use crate::{Term, ArenaTerm, Number, Name};
+use crate::name::name_length;
+use bumpalo::Bump;
+use std::fmt;
+use std::ops::Range;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    /// byte range of the offending input.
+    pub span: Range<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseErrorKind {
+    UnexpectedEnd,
+    UnexpectedChar(char),
+    InvalidNumber(String),
+    /// a `(` or `{` that's never closed.
+    Unclosed,
+    /// `d^ny/dt^m` with `n != m`.
+    MismatchedOrder,
+    /// a name that's taken by a constant, like `e`, where a variable has to go.
+    ReservedName(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use ParseErrorKind::*;
+
+        match &self.kind {
+            UnexpectedEnd => write!(f, "unexpected end of input")?,
+            UnexpectedChar(c) => write!(f, "unexpected '{c}'")?,
+            InvalidNumber(n) => write!(f, "invalid number {n}")?,
+            Unclosed => write!(f, "unclosed bracket")?,
+            MismatchedOrder => write!(f, "the orders of d^n and dt^n don't match")?,
+            ReservedName(name) => write!(f, "{name} is a constant, not a variable")?,
+        }
+
+        write!(f, " at {}..{}", self.span.start, self.span.end)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// the variable `y'`, `y''`, ... differentiate with respect to.
+const DEFAULT_TIME: &str = "t";
+
+pub(crate) const FUNCTIONS: [&str; 8] = ["sin", "cos", "exp", "ln", "heaviside", "dirac", "piecewise", "integral"];
+
+/// `₀`, `₁`, ... which stay part of a parameter's name instead of multiplying it.
+pub(crate) fn is_subscript(c: char) -> bool {
+    c.is_numeric() && !c.is_ascii_digit()
+}
+
+impl<'arena> Term<'arena> {
+    /// reads a term written the way `Display` prints them, or the way people do:
+    /// `3*sin(x^2) + e^(2t)`, `y'' + 2y' = x/4`, `d^2y/dt^2 - y`.
+    pub fn parse_in(input: &str, arena: &'arena Bump) -> Result<ArenaTerm<'arena>, ParseError> {
+        let mut parser = Parser { input, position: 0, arena };
+
+        let term = parser.sum()?;
+        parser.skip_whitespace();
+
+        match parser.peek() {
+            None => Ok(ArenaTerm::new_in(term, arena)),
+            Some(c) => Err(parser.error_here(ParseErrorKind::UnexpectedChar(c))),
+        }
+    }
+}
+
+struct Parser<'input, 'arena> {
+    input: &'input str,
+    position: usize,
+    arena: &'arena Bump,
+}
+
+impl<'arena> Parser<'_, 'arena> {
+    fn new_in(&self, term: Term<'arena>) -> ArenaTerm<'arena> {
+        ArenaTerm::new_in(term, self.arena)
+    }
+
+    fn rest(&self) -> &str {
+        &self.input[self.position..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    fn error_here(&self, kind: ParseErrorKind) -> ParseError {
+        let width = self.peek().map_or(0, char::len_utf8);
+        ParseError { kind, span: self.position..self.position + width }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), ParseError> {
+        self.skip_whitespace();
+
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.bump();
+                Ok(())
+            },
+            Some(c) => Err(self.error_here(ParseErrorKind::UnexpectedChar(c))),
+            None => Err(self.error_here(ParseErrorKind::UnexpectedEnd)),
+        }
+    }
+
+    /// `a + b`, `a - b`
+    fn sum(&mut self) -> Result<Term<'arena>, ParseError> {
+        use Term::*;
+
+        let mut sum = self.product()?;
+
+        loop {
+            self.skip_whitespace();
+
+            match self.peek() {
+                Some('+') => {
+                    self.bump();
+                    sum = Sum(self.new_in(sum), ArenaTerm::new_in(self.product()?, self.arena));
+                },
+
+                Some('-') => {
+                    self.bump();
+                    let negated = Negation(ArenaTerm::new_in(self.product()?, self.arena));
+                    sum = Sum(self.new_in(sum), self.new_in(negated));
+                },
+
+                _ => return Ok(sum),
+            }
+        }
+    }
+
+    /// `c*a` (a `Scale`), `a*b`, `a/b`, `ab`
+    fn product(&mut self) -> Result<Term<'arena>, ParseError> {
+        use Term::*;
+
+        let mut product = self.unary()?;
+
+        loop {
+            self.skip_whitespace();
+
+            product = match self.peek() {
+                Some('*') => {
+                    self.bump();
+
+                    /* a literal coefficient scales everything up to the next sum */
+                    if let Constant(coefficient) = product {
+                        return Ok(Scale { coefficient, term: ArenaTerm::new_in(self.product()?, self.arena) });
+                    }
+
+                    Product(self.new_in(product), ArenaTerm::new_in(self.unary()?, self.arena))
+                },
+
+                Some('/') => {
+                    self.bump();
+
+                    match (product, self.unary()?) {
+                        /* literal fractions like `1/3` are exact numbers */
+                        (Constant(numerator), Constant(denominator)) if !denominator.is_zero() => Constant(numerator / denominator),
+
+                        (Constant(one), denominator) if one.is_one() => Reciprocal(self.new_in(denominator)),
+                        (product, denominator) => Quotient(self.new_in(product), self.new_in(denominator)),
+                    }
+                },
+
+                Some(c) if c.is_alphanumeric() || c == '(' || c == '.' || c == '{' => Product(self.new_in(product), ArenaTerm::new_in(self.power()?, self.arena)),
+
+                _ => return Ok(product),
+            };
+        }
+    }
+
+    /// `-a`
+    fn unary(&mut self) -> Result<Term<'arena>, ParseError> {
+        use Term::*;
+
+        self.skip_whitespace();
+
+        if self.peek() != Some('-') {
+            return self.power();
+        }
+
+        self.bump();
+
+        Ok(match self.unary()? {
+            Constant(c) => Constant(-c),
+            term => Negation(self.new_in(term)),
+        })
+    }
+
+    /// `a^b`, right associative, with numeric bases turning into `Exponential`s.
+    fn power(&mut self) -> Result<Term<'arena>, ParseError> {
+        use Term::*;
+
+        let base = self.postfix()?;
+        self.skip_whitespace();
+
+        if self.peek() != Some('^') {
+            return Ok(base);
+        }
+
+        self.bump();
+        let exponent = ArenaTerm::new_in(self.unary()?, self.arena);
+
+        Ok(match base {
+            Constant(base) => Exponential(base, exponent),
+            E => Exponential(Number::E, exponent),
+            base => Power { base: self.new_in(base), exponent },
+        })
+    }
+
+    /// `a'`, `a''`, ...
+    fn postfix(&mut self) -> Result<Term<'arena>, ParseError> {
+        use Term::*;
+
+        let term = self.primary()?;
+        let mut order = 0;
+
+        while self.peek() == Some('\'') {
+            self.bump();
+            order += 1;
+        }
+
+        Ok(match order {
+            0 => term,
+            _ => Derivative { order, wrt: self.new_in(Var(Name::intern(DEFAULT_TIME))), term: self.new_in(term) },
+        })
+    }
+
+    fn primary(&mut self) -> Result<Term<'arena>, ParseError> {
+        use Term::*;
+
+        self.skip_whitespace();
+        let start = self.position;
+
+        match self.peek() {
+            None => Err(self.error_here(ParseErrorKind::UnexpectedEnd)),
+
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let length = self.rest().find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(self.rest().len());
+                let literal = &self.input[start..start + length];
+                self.position += length;
+
+                literal.parse().map(Constant).map_err(|_| ParseError {
+                    kind: ParseErrorKind::InvalidNumber(literal.to_string()),
+                    span: start..self.position,
+                })
+            },
+
+            Some('(') => {
+                self.bump();
+                let term = self.sum()?;
+                self.skip_whitespace();
+
+                /* `(term, wrt: x)'''`, the way derivatives get displayed */
+                if self.peek() == Some(',') {
+                    let wrt = self.wrt()?;
+                    self.close(start)?;
+
+                    let mut order = 0;
+                    while self.peek() == Some('\'') {
+                        self.bump();
+                        order += 1;
+                    }
+
+                    return Ok(Derivative { order, wrt: self.new_in(wrt), term: self.new_in(term) });
+                }
+
+                self.close(start)?;
+                Ok(term)
+            },
+
+            Some(c) if c.is_alphabetic() => {
+                if let Some(name) = FUNCTIONS.into_iter().find(|name| self.is_call(name)) {
+                    self.position += name.len();
+                    self.expect('(')?;
+
+                    let argument = ArenaTerm::new_in(self.sum()?, self.arena);
+
+                    /* `piecewise(switch, below, above)` */
+                    if name == "piecewise" {
+                        self.expect(',')?;
+                        let below = ArenaTerm::new_in(self.sum()?, self.arena);
+                        self.expect(',')?;
+                        let above = ArenaTerm::new_in(self.sum()?, self.arena);
+                        self.close(start)?;
+
+                        return Ok(Piecewise { switch: argument, below, above });
+                    }
+
+                    /* `integral(term, wrt: x)` */
+                    if name == "integral" {
+                        let wrt = ArenaTerm::new_in(self.wrt()?, self.arena);
+                        self.close(start)?;
+
+                        return Ok(Integral { wrt, term: argument });
+                    }
+
+                    self.close(start)?;
+
+                    return Ok(match name {
+                        "sin" => Sin(argument),
+                        "cos" => Cos(argument),
+                        "exp" => Exponential(Number::E, argument),
+                        "heaviside" => Heaviside(argument),
+                        "dirac" => Dirac(argument),
+                        _ => Ln(argument),
+                    });
+                }
+
+                if let Some(derivative) = self.leibniz()? {
+                    return Ok(derivative);
+                }
+
+                /* anything else is a product of variables like `x`, `theta` or `i_L` and greek parameters like `ω₀` */
+                if c.is_ascii() {
+                    let length = name_length(self.rest());
+                    let name = &self.input[start..start + length];
+                    self.position += length;
+
+                    return Ok(match name {
+                        "e" => E,
+                        "i" => I,
+                        _ => Var(Name::intern(name)),
+                    });
+                }
+
+                self.bump();
+
+                Ok(match c {
+                    'π' => Pi,
+                    _ => {
+                        let subscripts = self.rest().find(|c: char| !is_subscript(c)).unwrap_or(self.rest().len());
+                        self.position += subscripts;
+                        Symbol(self.arena.alloc_str(&self.input[start..self.position]))
+                    },
+                })
+            },
+
+            /* `{name}` for any other parameter */
+            Some('{') => {
+                self.bump();
+
+                let Some(length) = self.rest().find('}') else {
+                    return Err(ParseError { kind: ParseErrorKind::Unclosed, span: start..start + 1 });
+                };
+
+                if length == 0 {
+                    return Err(self.error_here(ParseErrorKind::UnexpectedChar('}')));
+                }
+
+                let name = self.arena.alloc_str(&self.rest()[..length]);
+                self.position += length + 1;
+                Ok(Symbol(name))
+            },
+
+            Some(c) => Err(self.error_here(ParseErrorKind::UnexpectedChar(c))),
+        }
+    }
+
+    fn close(&mut self, opened_at: usize) -> Result<(), ParseError> {
+        self.skip_whitespace();
+
+        match self.peek() {
+            Some(')') => {
+                self.bump();
+                Ok(())
+            },
+            Some(c) => Err(self.error_here(ParseErrorKind::UnexpectedChar(c))),
+            None => Err(ParseError { kind: ParseErrorKind::Unclosed, span: opened_at..opened_at + 1 }),
+        }
+    }
+
+    /// `, wrt: x`
+    fn wrt(&mut self) -> Result<Term<'arena>, ParseError> {
+        self.expect(',')?;
+        self.skip_whitespace();
+
+        if !self.rest().starts_with("wrt") {
+            return Err(self.error_here(ParseErrorKind::UnexpectedChar(self.peek().unwrap_or(','))));
+        }
+
+        self.position += "wrt".len();
+        self.expect(':')?;
+        self.sum()
+    }
+
+    fn is_call(&self, name: &str) -> bool {
+        self.rest().strip_prefix(name).is_some_and(|rest| rest.trim_start().starts_with('('))
+    }
+
+    /// `dy/dt` or `d^ny/dt^n`, if that's what comes next.
+    fn leibniz(&mut self) -> Result<Option<Term<'arena>>, ParseError> {
+        use Term::*;
+
+        let start = self.position;
+        let rest = self.rest();
+
+        /* the order of a `^n` at the start of `s`, and how long it's written */
+        let order_at = |s: &str| -> (usize, usize) {
+            match s.strip_prefix('^') {
+                Some(s) => {
+                    let digits = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+                    (s[..digits].parse().unwrap_or(0), 1 + digits)
+                },
+                None => (1, 0),
+            }
+        };
+
+        let Some(after_d) = rest.strip_prefix('d') else {
+            return Ok(None);
+        };
+
+        let (order, skipped) = order_at(after_d);
+        let y_at = 1 + skipped;
+        let y_length = name_length(&rest[y_at..]);
+
+        if order == 0 || y_length == 0 || !rest[y_at + y_length..].starts_with("/d") {
+            return Ok(None);
+        }
+
+        let t_at = y_at + y_length + 2;
+        let t_length = name_length(&rest[t_at..]);
+
+        if t_length == 0 {
+            return Ok(None);
+        }
+
+        let (wrt_order, skipped) = order_at(&rest[t_at + t_length..]);
+        let y = Name::intern(&rest[y_at..y_at + y_length]);
+        let t = Name::intern(&rest[t_at..t_at + t_length]);
+        self.position += t_at + t_length + skipped;
+
+        if wrt_order != order {
+            return Err(ParseError { kind: ParseErrorKind::MismatchedOrder, span: start..self.position });
+        }
+
+        Ok(Some(Derivative { order, wrt: self.new_in(Var(t)), term: self.new_in(Var(y)) }))
+    }
+}
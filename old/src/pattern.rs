@@ -0,0 +1,211 @@
+use crate::{Term, ArenaTerm, ParseError, ParseErrorKind, LadesError, Rewrite, Name};
+use crate::name::name_length;
+use bumpalo::Bump;
+use std::collections::HashMap;
+
+/// what each wildcard of a pattern matched.
+pub type Bindings<'arena> = HashMap<String, Term<'arena>>;
+
+/// a term whose `wildcards` match any subterm, as long as every occurrence of one matches the same thing.
+/// sums and products also match with their operands swapped.
+#[derive(Clone)]
+pub struct Pattern<'p> {
+    pub term: Term<'p>,
+    pub wildcards: Vec<String>,
+}
+
+impl<'p> Pattern<'p> {
+    pub fn new(term: Term<'p>, wildcards: &[&str]) -> Self {
+        Pattern { term, wildcards: wildcards.iter().map(|wildcard| wildcard.to_string()).collect() }
+    }
+
+    /// reads a pattern like `sin(?a)^2 + cos(?a)^2`, where `?x` marks the variable `x` as a wildcard.
+    pub fn parse_in(input: &str, arena: &'p Bump) -> Result<Self, ParseError> {
+        let mut wildcards = Vec::new();
+        let mut marks = Vec::new();
+        let mut stripped = String::with_capacity(input.len());
+
+        for (i, c) in input.char_indices() {
+            match name_length(&input[i + c.len_utf8()..]) {
+                length if c == '?' && length > 0 => {
+                    let wildcard = input[i + 1..i + 1 + length].to_string();
+                    marks.push(i);
+
+                    /* names that read as constants, like `?e`, would never match anything */
+                    if !matches!(Term::parse_in(&wildcard, arena).as_deref(), Ok(Term::Var(_))) {
+                        return Err(ParseError { kind: ParseErrorKind::ReservedName(wildcard), span: i..i + 1 + length });
+                    }
+
+                    if !wildcards.contains(&wildcard) {
+                        wildcards.push(wildcard);
+                    }
+                },
+                _ => stripped.push(c),
+            }
+        }
+
+        /* point errors at the original input */
+        let unstrip = |position: usize| position + marks.iter().enumerate().filter(|&(n, &mark)| mark - n <= position).count();
+
+        let term = Term::parse_in(&stripped, arena).map_err(|e| ParseError {
+            span: unstrip(e.span.start)..unstrip(e.span.end),
+            ..e
+        })?;
+
+        Ok(Pattern { term: *term, wildcards })
+    }
+
+    pub fn matches<'a>(&self, term: &Term<'a>) -> Option<Bindings<'a>> {
+        let mut bindings = Bindings::new();
+        self.match_term(&self.term, term, &mut bindings).then_some(bindings)
+    }
+
+    /// the pattern with its wildcards replaced by what they're bound to. unbound wildcards stay variables.
+    pub fn instantiate_in<'a>(&self, bindings: &Bindings<'a>, arena: &'a Bump) -> ArenaTerm<'a> {
+        let lookup = |leaf: &Term<'p>| match leaf {
+            Term::Var(var) if self.is_wildcard(*var) => bindings.get(var.as_str()).cloned(),
+            _ => None,
+        };
+
+        ArenaTerm::new_in(self.term.substitute_with(&lookup, arena), arena)
+    }
+
+    fn match_term<'a>(&self, pattern: &Term<'p>, term: &Term<'a>, bindings: &mut Bindings<'a>) -> bool {
+        use Term::*;
+
+        match (pattern, term) {
+            (Var(wildcard), _) if self.is_wildcard(*wildcard) => match bindings.get(wildcard.as_str()) {
+                Some(bound) => bound == term,
+                None => {
+                    bindings.insert(wildcard.to_string(), term.clone());
+                    true
+                },
+            },
+
+            (Constant(c1), Constant(c2)) => c1 == c2,
+            (Var(v1), Var(v2)) => v1 == v2,
+            (Symbol(s1), Symbol(s2)) => s1 == s2,
+            (Pi, Pi) | (E, E) | (I, I) => true,
+
+            (Quotient(p1, p2), Quotient(t1, t2)) => self.match_term(p1, t1, bindings) && self.match_term(p2, t2, bindings),
+            (Negation(p), Negation(t)) | (Reciprocal(p), Reciprocal(t)) => self.match_term(p, t, bindings),
+
+            (Sum(p1, p2), Sum(t1, t2)) | (Product(p1, p2), Product(t1, t2)) => {
+                let saved = bindings.clone();
+
+                if self.match_term(p1, t1, bindings) && self.match_term(p2, t2, bindings) {
+                    return true;
+                }
+
+                *bindings = saved;
+                self.match_term(p1, t2, bindings) && self.match_term(p2, t1, bindings)
+            },
+
+            (Scale { coefficient: c1, term: p }, Scale { coefficient: c2, term: t }) => c1 == c2 && self.match_term(p, t, bindings),
+
+            (Power { base: p1, exponent: p2 }, Power { base: t1, exponent: t2 }) => {
+                self.match_term(p1, t1, bindings) && self.match_term(p2, t2, bindings)
+            },
+
+            (Exponential(b1, p), Exponential(b2, t)) => b1 == b2 && self.match_term(p, t, bindings),
+
+            (Sin(p), Sin(t)) | (Cos(p), Cos(t)) | (Ln(p), Ln(t)) => self.match_term(p, t, bindings),
+            (Heaviside(p), Heaviside(t)) | (Dirac(p), Dirac(t)) => self.match_term(p, t, bindings),
+
+            (Piecewise { switch: p1, below: p2, above: p3 }, Piecewise { switch: t1, below: t2, above: t3 }) => {
+                self.match_term(p1, t1, bindings) && self.match_term(p2, t2, bindings) && self.match_term(p3, t3, bindings)
+            },
+
+            (Derivative { order: o1, wrt: p1, term: p2 }, Derivative { order: o2, wrt: t1, term: t2 }) => {
+                o1 == o2 && self.match_term(p1, t1, bindings) && self.match_term(p2, t2, bindings)
+            },
+
+            (Integral { wrt: p1, term: p2 }, Integral { wrt: t1, term: t2 }) => {
+                self.match_term(p1, t1, bindings) && self.match_term(p2, t2, bindings)
+            },
+
+            _ => false,
+        }
+    }
+
+    fn is_wildcard(&self, var: Name) -> bool {
+        self.wildcards.iter().any(|wildcard| wildcard == var.as_str())
+    }
+}
+
+impl<'arena> Term<'arena> {
+    /// the term with every occurrence of `var` replaced by `replacement`.
+    pub fn substitute_in(&self, var: Name, replacement: &Term<'arena>, arena: &'arena Bump) -> ArenaTerm<'arena> {
+        let lookup = |leaf: &Term<'arena>| (*leaf == Term::Var(var)).then(|| replacement.clone());
+        ArenaTerm::new_in(self.substitute_with(&lookup, arena), arena)
+    }
+
+    /// the term with the parameter `name` replaced by `replacement`, e.g. a numeric value for `{k}`.
+    pub fn substitute_symbol_in(&self, name: &str, replacement: &Term<'arena>, arena: &'arena Bump) -> ArenaTerm<'arena> {
+        let lookup = |leaf: &Term<'arena>| matches!(leaf, Term::Symbol(s) if *s == name).then(|| replacement.clone());
+        ArenaTerm::new_in(self.substitute_with(&lookup, arena), arena)
+    }
+
+    /// a copy of the term in `arena`, with the variables and parameters `lookup` knows replaced (all at once).
+    fn substitute_with<'b>(&self, lookup: &impl Fn(&Term<'arena>) -> Option<Term<'b>>, arena: &'b Bump) -> Term<'b> {
+        use Term::*;
+
+        let sub = |term: &Term<'arena>| ArenaTerm::new_in(term.substitute_with(lookup, arena), arena);
+
+        /* what a derivative or integral is taken with respect to has to stay a variable, so it's only renamed */
+        let sub_wrt = |wrt: &Term<'arena>| ArenaTerm::new_in(match (wrt, wrt.substitute_with(lookup, arena)) {
+            (_, var @ Var(_)) => var,
+            (Var(v), _) => Var(*v),
+            (_, substituted) => substituted,
+        }, arena);
+
+        match self {
+            Constant(c) => Constant(*c),
+            Var(v) => lookup(self).unwrap_or(Var(*v)),
+            Symbol(name) => lookup(self).unwrap_or_else(|| Symbol(arena.alloc_str(name))),
+            Pi => Pi,
+            E => E,
+            I => I,
+
+            Sum(t1, t2) => Sum(sub(t1), sub(t2)),
+            Product(t1, t2) => Product(sub(t1), sub(t2)),
+            Quotient(t1, t2) => Quotient(sub(t1), sub(t2)),
+            Negation(term) => Negation(sub(term)),
+            Reciprocal(term) => Reciprocal(sub(term)),
+            Scale { coefficient, term } => Scale { coefficient: *coefficient, term: sub(term) },
+            Power { base, exponent } => Power { base: sub(base), exponent: sub(exponent) },
+            Exponential(base, term) => Exponential(*base, sub(term)),
+
+            Sin(term) => Sin(sub(term)),
+            Cos(term) => Cos(sub(term)),
+            Ln(term) => Ln(sub(term)),
+            Heaviside(term) => Heaviside(sub(term)),
+            Dirac(term) => Dirac(sub(term)),
+            Piecewise { switch, below, above } => Piecewise { switch: sub(switch), below: sub(below), above: sub(above) },
+
+            Derivative { order, wrt, term } => Derivative { order: *order, wrt: sub_wrt(wrt), term: sub(term) },
+            Integral { wrt, term } => Integral { wrt: sub_wrt(wrt), term: sub(term) },
+        }
+    }
+}
+
+impl Rewrite {
+    /// a rule rewriting whatever matches `pattern` into `replacement`, e.g. `ln(?a) + ln(?b)` into `ln(?a?b)`.
+    /// both are checked here, but since rules outlive any one arena, they're parsed again into the arena of
+    /// every term the rule looks at.
+    pub fn from_patterns(name: impl Into<String>, pattern: &str, replacement: &str) -> Result<Self, LadesError> {
+        let arena = Bump::new();
+        Pattern::parse_in(pattern, &arena)?;
+        Pattern::parse_in(replacement, &arena)?;
+
+        let (pattern, replacement) = (pattern.to_string(), replacement.to_string());
+
+        Ok(Rewrite::new(name, move |term, arena| {
+            let Some(bindings) = Pattern::parse_in(&pattern, arena)?.matches(term) else {
+                return Ok(None);
+            };
+
+            Ok(Some(*Pattern::parse_in(&replacement, arena)?.instantiate_in(&bindings, arena)))
+        }))
+    }
+}
@@ -0,0 +1,268 @@
+use crate::{Term, ArenaTerm, LadesError, Number, Name};
+use bumpalo::Bump;
+
+/// a polynomial in one variable with `Term` coefficients, stored densely, lowest power first.
+/// coefficients are kept in canonical form, which is what decides whether one is zero, and
+/// there are no trailing zeros, so the zero polynomial has no coefficients at all.
+#[derive(Clone, PartialEq)]
+pub struct Polynomial<'arena> {
+    var: Name,
+    coefficients: Vec<Term<'arena>>,
+}
+
+impl<'arena> Term<'arena> {
+    /// the term as a polynomial in `var`, with whatever doesn't mention `var` as coefficients.
+    pub fn polynomial_in(&self, var: Term, arena: &'arena Bump) -> Result<Polynomial<'arena>, LadesError> {
+        match var {
+            Term::Var(x) => self.polynomial(x, arena),
+            var => Err(LadesError::NonVariable(var.to_string())),
+        }
+    }
+
+    fn polynomial(&self, x: Name, arena: &'arena Bump) -> Result<Polynomial<'arena>, LadesError> {
+        use Term::*;
+
+        let non_polynomial = || LadesError::NonPolynomial(self.to_string());
+
+        if !self.contains_var(x) {
+            return Polynomial::new_in(x, vec![self.clone()], arena);
+        }
+
+        match self {
+            Var(_) => Polynomial::new_in(x, vec![Constant(Number::from(0)), Constant(Number::from(1))], arena),
+
+            Sum(t1, t2) => t1.polynomial(x, arena)?.add_in(&t2.polynomial(x, arena)?, arena),
+            Product(t1, t2) => t1.polynomial(x, arena)?.mul_in(&t2.polynomial(x, arena)?, arena),
+            Scale { coefficient, term } => term.polynomial(x, arena)?.scale_in(&Constant(*coefficient), arena),
+            Negation(term) => term.polynomial(x, arena)?.scale_in(&Constant(Number::from(-1)), arena),
+
+            /* dividing by a coefficient is fine, dividing by `x` isn't */
+            Quotient(numerator, denominator) if !denominator.contains_var(x) => {
+                numerator.polynomial(x, arena)?.scale_in(&Reciprocal(denominator.clone()), arena)
+            },
+
+            Power { base, exponent } => {
+                let Constant(n) = **exponent else {
+                    return Err(non_polynomial());
+                };
+
+                let n = n.to_integer().and_then(|n| usize::try_from(n).ok()).ok_or_else(non_polynomial)?;
+                let base = base.polynomial(x, arena)?;
+
+                (0..n).try_fold(Polynomial::new_in(x, vec![Constant(Number::from(1))], arena)?, |power, _| power.mul_in(&base, arena))
+            },
+
+            _ => Err(non_polynomial()),
+        }
+    }
+}
+
+impl<'arena> Polynomial<'arena> {
+    /// `sum(coefficients[k] var^k)`
+    pub fn new_in(var: Name, coefficients: Vec<Term<'arena>>, arena: &'arena Bump) -> Result<Self, LadesError> {
+        let coefficients = coefficients.into_iter().map(|c| canonical(c, arena)).collect::<Result<_, _>>()?;
+        Ok(Polynomial { var, coefficients }.trimmed())
+    }
+
+    pub fn var(&self) -> Name {
+        self.var
+    }
+
+    /// every coefficient, lowest power first.
+    pub fn coefficients(&self) -> &[Term<'arena>] {
+        &self.coefficients
+    }
+
+    /// the nonzero coefficients with their powers, lowest power first.
+    pub fn terms(&self) -> impl Iterator<Item = (usize, &Term<'arena>)> {
+        self.coefficients.iter().enumerate().filter(|(_, c)| !is_zero(c))
+    }
+
+    /// `None` for the zero polynomial.
+    pub fn degree(&self) -> Option<usize> {
+        self.coefficients.len().checked_sub(1)
+    }
+
+    pub fn leading_coefficient(&self) -> Option<&Term<'arena>> {
+        self.coefficients.last()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.coefficients.is_empty()
+    }
+
+    pub fn add_in(&self, other: &Polynomial<'arena>, arena: &'arena Bump) -> Result<Self, LadesError> {
+        let var = self.shared_var(other)?;
+
+        let (long, short) = match self.coefficients.len() >= other.coefficients.len() {
+            true => (self, other),
+            false => (other, self),
+        };
+
+        let mut coefficients = long.coefficients.clone();
+        for (sum, c) in coefficients.iter_mut().zip(&short.coefficients) {
+            *sum = binary(Term::Sum, sum.clone(), c.clone(), arena)?;
+        }
+
+        Ok(Polynomial { var, coefficients }.trimmed())
+    }
+
+    pub fn sub_in(&self, other: &Polynomial<'arena>, arena: &'arena Bump) -> Result<Self, LadesError> {
+        self.add_in(&other.scale_in(&Term::Constant(Number::from(-1)), arena)?, arena)
+    }
+
+    pub fn mul_in(&self, other: &Polynomial<'arena>, arena: &'arena Bump) -> Result<Self, LadesError> {
+        let var = self.shared_var(other)?;
+
+        if self.is_zero() || other.is_zero() {
+            return Ok(Polynomial { var, coefficients: Vec::new() });
+        }
+
+        let mut coefficients = vec![Term::Constant(Number::from(0)); self.coefficients.len() + other.coefficients.len() - 1];
+
+        for (i, a) in self.terms() {
+            for (j, b) in other.terms() {
+                let product = binary(Term::Product, a.clone(), b.clone(), arena)?;
+                coefficients[i + j] = binary(Term::Sum, coefficients[i + j].clone(), product, arena)?;
+            }
+        }
+
+        Ok(Polynomial { var, coefficients }.trimmed())
+    }
+
+    /// every coefficient multiplied by `c`, which shouldn't mention the variable.
+    pub fn scale_in(&self, c: &Term<'arena>, arena: &'arena Bump) -> Result<Self, LadesError> {
+        let coefficients = self.coefficients.iter()
+            .map(|a| binary(Term::Product, c.clone(), a.clone(), arena))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Polynomial { var: self.var, coefficients }.trimmed())
+    }
+
+    /// the quotient and remainder of long division, with the remainder's degree below the divisor's.
+    pub fn div_rem_in(&self, divisor: &Polynomial<'arena>, arena: &'arena Bump) -> Result<(Self, Self), LadesError> {
+        let var = self.shared_var(divisor)?;
+
+        let Some(leading) = divisor.leading_coefficient() else {
+            return Err(LadesError::DivisionByZero(self.to_term_in(arena).to_string()));
+        };
+
+        let n = divisor.coefficients.len();
+        let mut remainder = self.coefficients.clone();
+        let mut quotient = vec![Term::Constant(Number::from(0)); remainder.len().saturating_sub(n - 1)];
+
+        while remainder.len() >= n {
+            let shift = remainder.len() - n;
+            let q = binary(Term::Quotient, remainder.last().unwrap().clone(), leading.clone(), arena)?;
+
+            for (k, d) in divisor.coefficients.iter().enumerate().take(n - 1) {
+                let product = binary(Term::Product, q.clone(), d.clone(), arena)?;
+                remainder[shift + k] = binary(Term::Sum, remainder[shift + k].clone(), Term::Negation(ArenaTerm::new_in(product, arena)), arena)?;
+            }
+
+            /* the leading terms cancel by construction, whether or not canonical form can tell */
+            remainder.pop();
+            quotient[shift] = q;
+        }
+
+        Ok((
+            Polynomial { var, coefficients: quotient }.trimmed(),
+            Polynomial { var, coefficients: remainder }.trimmed(),
+        ))
+    }
+
+    /// the monic greatest common divisor, by euclid's algorithm. zero only if both are.
+    pub fn gcd_in(&self, other: &Polynomial<'arena>, arena: &'arena Bump) -> Result<Self, LadesError> {
+        let (mut a, mut b) = (self.clone(), other.clone());
+
+        while !b.is_zero() {
+            let (_, remainder) = a.div_rem_in(&b, arena)?;
+            (a, b) = (b, remainder);
+        }
+
+        match a.leading_coefficient() {
+            Some(leading) => a.scale_in(&Term::Reciprocal(ArenaTerm::new_in(leading.clone(), arena)), arena),
+            None => Ok(a),
+        }
+    }
+
+    /// the derivative with respect to the variable.
+    pub fn derivative_in(&self, arena: &'arena Bump) -> Result<Self, LadesError> {
+        let coefficients = self.coefficients.iter().enumerate().skip(1)
+            .map(|(k, c)| canonical(Term::Scale { coefficient: Number::from(k), term: ArenaTerm::new_in(c.clone(), arena) }, arena))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Polynomial { var: self.var, coefficients }.trimmed())
+    }
+
+    /// the value at `x`, by horner's scheme, in canonical form.
+    pub fn eval_in(&self, x: &Term<'arena>, arena: &'arena Bump) -> Result<ArenaTerm<'arena>, LadesError> {
+        let value = self.coefficients.iter().rev().try_fold(Term::Constant(Number::from(0)), |value, c| {
+            binary(Term::Sum, binary(Term::Product, value, x.clone(), arena)?, c.clone(), arena)
+        })?;
+
+        Ok(ArenaTerm::new_in(value, arena))
+    }
+
+    /// the polynomial as a sum of `c*var^k`, highest power first, leaving out zero coefficients.
+    pub fn to_term_in(&self, arena: &'arena Bump) -> ArenaTerm<'arena> {
+        use Term::*;
+
+        let new_in = |term| ArenaTerm::new_in(term, arena);
+
+        self.coefficients.iter().enumerate().rev()
+            .filter(|(_, c)| !is_zero(c))
+            .map(|(k, c)| {
+                let power = match k {
+                    0 => return new_in(c.clone()),
+                    1 => Var(self.var),
+                    _ => Power { base: new_in(Var(self.var)), exponent: new_in(Constant(Number::from(k))) },
+                };
+
+                new_in(match c {
+                    Constant(c) if c.is_one() => power,
+                    Constant(c) => Scale { coefficient: *c, term: new_in(power) },
+                    Scale { coefficient, term } => Scale { coefficient: *coefficient, term: new_in(Product(term.clone(), new_in(power))) },
+                    c => Product(new_in(c.clone()), new_in(power)),
+                })
+            })
+            .reduce(|sum, term| new_in(Sum(sum, term)))
+            .unwrap_or_else(|| new_in(Constant(Number::from(0))))
+    }
+
+    fn trimmed(mut self) -> Self {
+        while self.coefficients.last().is_some_and(is_zero) {
+            self.coefficients.pop();
+        }
+
+        self
+    }
+
+    /* constants are polynomials in any variable */
+    fn shared_var(&self, other: &Polynomial<'arena>) -> Result<Name, LadesError> {
+        match (self.degree().unwrap_or(0), other.degree().unwrap_or(0)) {
+            _ if self.var == other.var => Ok(self.var),
+            (0, _) => Ok(other.var),
+            (_, 0) => Ok(self.var),
+            _ => Err(LadesError::Unsupported(format!("polynomials in both {} and {}", self.var, other.var))),
+        }
+    }
+}
+
+fn canonical<'arena>(term: Term<'arena>, arena: &'arena Bump) -> Result<Term<'arena>, LadesError> {
+    Ok(*term.canonicalize_in(arena)?)
+}
+
+/// `op(a, b)` in canonical form.
+fn binary<'arena>(
+    op: fn(ArenaTerm<'arena>, ArenaTerm<'arena>) -> Term<'arena>,
+    a: Term<'arena>,
+    b: Term<'arena>,
+    arena: &'arena Bump
+) -> Result<Term<'arena>, LadesError> {
+    canonical(op(ArenaTerm::new_in(a, arena), ArenaTerm::new_in(b, arena)), arena)
+}
+
+fn is_zero(c: &Term) -> bool {
+    matches!(c, Term::Constant(c) if c.is_zero())
+}
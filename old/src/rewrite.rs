@@ -0,0 +1,169 @@
+use crate::{Term, ArenaTerm, LadesError};
+use bumpalo::Bump;
+
+type RewriteFn = dyn for<'a> Fn(&Term<'a>, &'a Bump) -> Result<Option<Term<'a>>, LadesError> + Send + Sync;
+
+/// a named rewrite rule. the function is both the pattern and the replacement:
+/// it returns the rewritten term if the pattern matches, `None` otherwise.
+pub struct Rewrite {
+    name: String,
+    rewrite: Box<RewriteFn>,
+}
+
+impl Rewrite {
+    pub fn new(
+        name: impl Into<String>,
+        rewrite: impl for<'a> Fn(&Term<'a>, &'a Bump) -> Result<Option<Term<'a>>, LadesError> + Send + Sync + 'static,
+    ) -> Self {
+        Rewrite { name: name.into(), rewrite: Box::new(rewrite) }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn apply<'a>(&self, term: &Term<'a>, arena: &'a Bump) -> Result<Option<Term<'a>>, LadesError> {
+        (self.rewrite)(term, arena)
+    }
+}
+
+/// an ordered registry of rules. at every node the first rule that matches wins.
+pub struct Rules {
+    rules: Vec<Rewrite>,
+    /// how many bottom-up passes to make before giving up on reaching a fixed point.
+    pub max_iterations: usize,
+}
+
+impl Rules {
+    pub fn empty() -> Self {
+        Rules { rules: Vec::new(), max_iterations: 100 }
+    }
+
+    pub fn register(&mut self, rule: Rewrite) -> &mut Self {
+        self.rules.push(rule);
+        self
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Rewrite> {
+        let index = self.rules.iter().position(|rule| rule.name == name)?;
+        Some(self.rules.remove(index))
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.rules.iter().map(Rewrite::name)
+    }
+}
+
+/// gets told about every step of a simplification.
+pub trait SimplifyObserver<'arena> {
+    fn enter(&mut self, _term: &Term<'arena>, _depth: usize) {}
+    fn rewrite(&mut self, _rule: &str, _before: &Term<'arena>, _after: &Term<'arena>, _depth: usize) {}
+    fn leave(&mut self, _term: &Term<'arena>, _depth: usize) {}
+}
+
+impl SimplifyObserver<'_> for () {}
+
+/// prints the nested `<simplifying ...> {` blocks `debug_simplify_in` shows.
+pub struct PrintObserver;
+
+impl<'arena> SimplifyObserver<'arena> for PrintObserver {
+    fn enter(&mut self, term: &Term<'arena>, depth: usize) {
+        println!("{}<simplifying {}> {{", "   ".repeat(depth), term);
+    }
+
+    fn rewrite(&mut self, rule: &str, before: &Term<'arena>, after: &Term<'arena>, depth: usize) {
+        println!("{}{before} => {after} ({rule})", "   ".repeat(depth + 1));
+    }
+
+    fn leave(&mut self, _term: &Term<'arena>, depth: usize) {
+        println!("{}}}", "   ".repeat(depth));
+    }
+}
+
+impl<'arena> Term<'arena> {
+    /// rewrites the term bottom-up with `rules` until none of them match anymore. if they still do after
+    /// `rules.max_iterations` passes, e.g. because two rules undo each other, that's a `NotConverged` error.
+    pub fn simplify_with(
+        self,
+        rules: &Rules,
+        observer: &mut impl SimplifyObserver<'arena>,
+        arena: &'arena Bump,
+    ) -> Result<ArenaTerm<'arena>, LadesError> {
+        let mut term = self;
+
+        for _ in 0..rules.max_iterations {
+            let (rewritten, changed) = term.rewrite_pass(rules, observer, 0, arena)?;
+            term = rewritten;
+
+            if !changed {
+                return Ok(ArenaTerm::new_in(term, arena));
+            }
+        }
+
+        Err(LadesError::NotConverged(term.to_string()))
+    }
+
+    /// rewrites the children, then applies at most one rule to the node itself.
+    fn rewrite_pass(
+        self,
+        rules: &Rules,
+        observer: &mut impl SimplifyObserver<'arena>,
+        depth: usize,
+        arena: &'arena Bump,
+    ) -> Result<(Term<'arena>, bool), LadesError> {
+        observer.enter(&self, depth);
+
+        let mut changed = false;
+        let mut term = self.try_map_children(|child| {
+            let (child, child_changed) = child.rewrite_pass(rules, observer, depth + 1, arena)?;
+            changed |= child_changed;
+            Ok(child)
+        }, arena)?;
+
+        for rule in &rules.rules {
+            if let Some(rewritten) = rule.apply(&term, arena)? {
+                observer.rewrite(&rule.name, &term, &rewritten, depth);
+                term = rewritten;
+                changed = true;
+                break;
+            }
+        }
+
+        observer.leave(&term, depth);
+        Ok((term, changed))
+    }
+
+    /// the same node with `f` applied to each of its direct subterms.
+    pub(crate) fn try_map_children(
+        self,
+        mut f: impl FnMut(Term<'arena>) -> Result<Term<'arena>, LadesError>,
+        arena: &'arena Bump,
+    ) -> Result<Term<'arena>, LadesError> {
+        use Term::*;
+
+        let mut map = |term: ArenaTerm<'arena>| f(*term).map(|term| ArenaTerm::new_in(term, arena));
+
+        Ok(match self {
+            Constant(_) | Var(_) | Symbol(_) | Pi | E | I => self,
+
+            Sum(t1, t2) => Sum(map(t1)?, map(t2)?),
+            Product(t1, t2) => Product(map(t1)?, map(t2)?),
+            Quotient(t1, t2) => Quotient(map(t1)?, map(t2)?),
+            Negation(term) => Negation(map(term)?),
+            Reciprocal(term) => Reciprocal(map(term)?),
+            Scale { coefficient, term } => Scale { coefficient, term: map(term)? },
+            Power { base, exponent } => Power { base: map(base)?, exponent: map(exponent)? },
+            Exponential(base, term) => Exponential(base, map(term)?),
+
+            Sin(term) => Sin(map(term)?),
+            Cos(term) => Cos(map(term)?),
+            Ln(term) => Ln(map(term)?),
+            Heaviside(term) => Heaviside(map(term)?),
+            Dirac(term) => Dirac(map(term)?),
+            Piecewise { switch, below, above } => Piecewise { switch: map(switch)?, below: map(below)?, above: map(above)? },
+
+            Derivative { order, wrt, term } => Derivative { order, wrt: map(wrt)?, term: map(term)? },
+            Integral { wrt, term } => Integral { wrt: map(wrt)?, term: map(term)? },
+        })
+    }
+}
@@ -0,0 +1,647 @@
+use crate::{Term, ArenaTerm, LadesError, Number, Name, Polynomial};
+use crate::complex::Complex;
+use crate::inverse_laplace::tidy;
+use crate::trigonometry::times;
+use bumpalo::Bump;
+
+/// a root of a polynomial, as its real and imaginary parts, and how many times it's repeated.
+#[derive(Clone, PartialEq)]
+pub struct Root<'arena> {
+    pub re: Term<'arena>,
+    pub im: Term<'arena>,
+    pub multiplicity: usize,
+}
+
+impl<'arena> Polynomial<'arena> {
+    /// every distinct root of a polynomial with numeric coefficients. rational roots, and the quadratic
+    /// that may be left once they're divided out, come out exact (with square roots as `b^0.5`), and so do
+    /// cubics with a single real root and quartics in `x^2` alone, in radicals. cubics with three irrational
+    /// real roots, which would take cosines of arccosines, other quartics and higher degrees are numeric.
+    pub fn roots_in(&self, arena: &'arena Bump) -> Result<Vec<Root<'arena>>, LadesError> {
+        let unsupported = || LadesError::Unsupported(format!("the roots of {}", self.to_term_in(arena)));
+
+        if self.is_zero() {
+            return Err(unsupported());
+        }
+
+        let mut coefficients = self.coefficients().iter()
+            .map(|c| match c {
+                Term::Constant(c) => Some(*c),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(unsupported)?;
+
+        let mut roots = Vec::new();
+
+        /* x^k divides out without looking at the rest */
+        let zeros = coefficients.iter().take_while(|c| c.is_zero()).count();
+        if zeros > 0 {
+            roots.push(Root::real(Term::Constant(Number::from(0)), zeros));
+            coefficients.drain(..zeros);
+        }
+
+        if let Some(mut integers) = integer_coefficients(&coefficients) {
+            for (root, multiplicity) in rational_roots(&mut integers) {
+                roots.push(Root::real(Term::Constant(root), multiplicity));
+            }
+
+            coefficients = integers.into_iter().map(|n| Number::Rational(n, 1)).collect();
+        }
+
+        match coefficients.len() {
+            0 | 1 => {},
+            2 => roots.push(Root::real(Term::Constant(-coefficients[0] / coefficients[1]), 1)),
+            3 => roots.extend(quadratic_roots(coefficients[2], coefficients[1], coefficients[0], arena)),
+
+            n => {
+                let exact = match n {
+                    _ if coefficients.iter().any(|c| !c.is_exact()) => None,
+                    4 => cardano(&coefficients, arena),
+                    5 => biquadratic(&coefficients, arena),
+                    _ => None,
+                };
+
+                match exact {
+                    Some(exact) => roots.extend(exact),
+                    None => {
+                        let p: Vec<f64> = coefficients.iter().map(|c| c.to_f64()).collect();
+
+                        roots.extend(numeric_roots(&p).into_iter().map(|(root, multiplicity)| Root {
+                            re: Term::Constant(tidy(root.re)),
+                            im: Term::Constant(tidy(root.im)),
+                            multiplicity,
+                        }));
+                    },
+                }
+            },
+        }
+
+        Ok(roots)
+    }
+}
+
+impl<'arena> Root<'arena> {
+    fn real(re: Term<'arena>, multiplicity: usize) -> Self {
+        Root { re, im: Term::Constant(Number::from(0)), multiplicity }
+    }
+
+    fn imaginary(im: Term<'arena>, multiplicity: usize) -> Self {
+        Root { re: Term::Constant(Number::from(0)), im, multiplicity }
+    }
+}
+
+/// the roots of a polynomial (lowest power first) as `roots_in` finds them, evaluated for the solvers that work
+/// in floating point and ordered like `numeric_roots`.
+pub(crate) fn evaluated_roots(coefficients: &[Number], arena: &Bump) -> Result<Vec<(Complex, usize)>, LadesError> {
+    let s = Name::intern("s");
+    let polynomial = Polynomial::new_in(s, coefficients.iter().map(|&c| Term::Constant(c)).collect(), arena)?;
+
+    let mut roots = polynomial.roots_in(arena)?.into_iter()
+        .map(|Root { re, im, multiplicity }| Some((Complex::new(re.eval(&|_| None).ok()?, im.eval(&|_| None).ok()?), multiplicity)))
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| LadesError::Unsupported(format!("the roots of {}", polynomial.to_term_in(arena))))?;
+
+    roots.sort_by(|(z1, _), (z2, _)| z2.re.total_cmp(&z1.re).then(z2.im.total_cmp(&z1.im)));
+    Ok(roots)
+}
+
+/// the roots of `ax^2 + bx + c`, as `-b/2a ± sqrt(b^2 - 4ac)/2a`.
+fn quadratic_roots<'arena>(a: Number, b: Number, c: Number, arena: &'arena Bump) -> Vec<Root<'arena>> {
+    use Term::*;
+
+    let two_a = Number::from(2) * a;
+    let center = -b / two_a;
+    let discriminant = b * b - Number::from(4) * a * c;
+
+    if discriminant.is_zero() {
+        return vec![Root::real(Constant(center), 2)];
+    }
+
+    let half_width = radical(discriminant.abs(), 2, Number::from(1) / two_a.abs(), arena);
+    let negated = Negation(ArenaTerm::new_in(half_width.clone(), arena));
+
+    let shifted = |offset: Term<'arena>| match center {
+        _ if center.is_zero() => offset,
+        _ => Sum(ArenaTerm::new_in(Constant(center), arena), ArenaTerm::new_in(offset, arena)),
+    };
+
+    match discriminant.is_positive() {
+        true => vec![Root::real(shifted(half_width), 1), Root::real(shifted(negated), 1)],
+        false => vec![
+            Root { re: Constant(center), im: half_width, multiplicity: 1 },
+            Root { re: Constant(center), im: negated, multiplicity: 1 },
+        ],
+    }
+}
+
+/// the roots of a cubic (lowest power first) with one real root and a complex pair, by cardano's formula. with
+/// `x = y - a/3` turning `x^3 + ax^2 + bx + c` into `y^3 + py + q`, the real root is `y = u + v` for
+/// `u^3, v^3 = -q/2 ± sqrt(q^2/4 + p^3/27)`, and the pair is `-(u + v)/2 ± i sqrt(3)/2 (u - v)`. `None` when the
+/// discriminant isn't positive: the roots are all real then, and irrational ones take more than real radicals.
+fn cardano<'arena>(coefficients: &[Number], arena: &'arena Bump) -> Option<Vec<Root<'arena>>> {
+    use Term::*;
+
+    let [c, b, a] = [0, 1, 2].map(|k| coefficients[k] / coefficients[3]);
+    let third = Number::ratio(1, 3).unwrap();
+
+    let p = b - a * a * third;
+    let q = Number::from(2) * a * a * a / Number::from(27) - a * b * third + c;
+    let discriminant = q * q / Number::from(4) + p * p * p / Number::from(27);
+
+    if !discriminant.is_exact() || !discriminant.is_positive() {
+        return None;
+    }
+
+    let u = real_root(Surd { rational: -q / Number::from(2), coefficient: Number::from(1), radicand: discriminant }, 3, arena);
+    let v = real_root(Surd { rational: -q / Number::from(2), coefficient: Number::from(-1), radicand: discriminant }, 3, arena);
+    let shift = Constant(-a * third);
+
+    let re = plus(shift.clone(), times(Number::ratio(-1, 2).unwrap(), plus(u.clone(), v.clone(), arena), arena), arena);
+    let im = match plus(u.clone(), negated(v.clone(), arena), arena) {
+        Constant(width) => radical(Number::from(3), 2, width / Number::from(2), arena),
+        width => times(Number::ratio(1, 2).unwrap(), Product(ArenaTerm::new_in(radical(Number::from(3), 2, Number::from(1), arena), arena), ArenaTerm::new_in(width, arena)), arena),
+    };
+
+    Some(vec![
+        Root::real(plus(plus(shift, u, arena), v, arena), 1),
+        Root { re: re.clone(), im: im.clone(), multiplicity: 1 },
+        Root { re, im: negated(im, arena), multiplicity: 1 },
+    ])
+}
+
+/// the roots of `x^4 + bx^2 + d` (lowest power first), as the square roots of the roots `z` of `z^2 + bz + d`.
+/// when those are complex, `|z| = sqrt(d)` and `sqrt(z) = sqrt((sqrt(d) + Re(z))/2) ± i sqrt((sqrt(d) - Re(z))/2)`.
+fn biquadratic<'arena>(coefficients: &[Number], arena: &'arena Bump) -> Option<Vec<Root<'arena>>> {
+    if !coefficients[1].is_zero() || !coefficients[3].is_zero() {
+        return None;
+    }
+
+    let (b, d) = (coefficients[2] / coefficients[4], coefficients[0] / coefficients[4]);
+    let center = -b / Number::from(2);
+    let discriminant = b * b - Number::from(4) * d;
+    let half = Number::ratio(1, 2).unwrap();
+
+    /* ±sqrt(z) for a real z, repeated `multiplicity` times */
+    let square_roots = |z: Surd, multiplicity| {
+        let (root, imaginary) = match z.value() > 0.0 {
+            true => (real_root(z, 2, arena), false),
+            false => (real_root(-z, 2, arena), true),
+        };
+
+        [root.clone(), negated(root, arena)].map(|root| match imaginary {
+            true => Root::imaginary(root, multiplicity),
+            false => Root::real(root, multiplicity),
+        })
+    };
+
+    let zero = Number::from(0);
+
+    if !discriminant.is_exact() {
+        return None;
+    }
+
+    if discriminant.is_zero() {
+        return Some(square_roots(Surd { rational: center, coefficient: zero, radicand: zero }, 2).to_vec());
+    }
+
+    if discriminant.is_positive() {
+        return Some([half, -half].into_iter()
+            .flat_map(|sign| square_roots(Surd { rational: center, coefficient: sign, radicand: discriminant }, 1))
+            .collect());
+    }
+
+    let re = real_root(Surd { rational: center * half, coefficient: half, radicand: d }, 2, arena);
+    let im = real_root(Surd { rational: -center * half, coefficient: half, radicand: d }, 2, arena);
+
+    Some([re.clone(), negated(re, arena)].into_iter()
+        .flat_map(|re| [im.clone(), negated(im.clone(), arena)].map(|im| Root { re: re.clone(), im, multiplicity: 1 }))
+        .collect())
+}
+
+/// `rational + coefficient*sqrt(radicand)`, with a nonnegative radicand.
+#[derive(Clone, Copy)]
+struct Surd {
+    rational: Number,
+    coefficient: Number,
+    radicand: Number,
+}
+
+impl Surd {
+    fn value(self) -> f64 {
+        self.rational.to_f64() + self.coefficient.to_f64() * self.radicand.to_f64().sqrt()
+    }
+
+    /// the value as a number, unless that would take an irrational square root.
+    fn exact(self) -> Option<Number> {
+        let root = self.radicand.sqrt();
+
+        match () {
+            _ if self.coefficient.is_zero() => Some(self.rational),
+            _ if root.is_exact() => Some(self.rational + self.coefficient * root),
+            _ => None,
+        }
+    }
+
+    fn to_term(self, arena: &Bump) -> Term<'_> {
+        let root = match self.coefficient.is_negative() {
+            true => negated(radical(self.radicand, 2, -self.coefficient, arena), arena),
+            false => radical(self.radicand, 2, self.coefficient, arena),
+        };
+
+        plus(Term::Constant(self.rational), root, arena)
+    }
+}
+
+impl std::ops::Neg for Surd {
+    type Output = Surd;
+
+    fn neg(self) -> Surd {
+        Surd { rational: -self.rational, coefficient: -self.coefficient, ..self }
+    }
+}
+
+/// the real `n`th root of `x`, which has to be nonnegative for even `n`.
+fn real_root(x: Surd, n: u32, arena: &Bump) -> Term<'_> {
+    use Term::*;
+
+    if x.value() < 0.0 {
+        return negated(real_root(-x, n, arena), arena);
+    }
+
+    match x.exact() {
+        Some(x) => radical(x, n, Number::from(1), arena),
+
+        /* (c sqrt(r))^(1/n) = (c^2 r)^(1/2n) */
+        None if x.rational.is_zero() => radical(x.coefficient * x.coefficient * x.radicand, 2 * n, Number::from(1), arena),
+
+        None => Power {
+            base: ArenaTerm::new_in(x.to_term(arena), arena),
+            exponent: ArenaTerm::new_in(Constant(Number::ratio(1, n as i128).unwrap()), arena),
+        },
+    }
+}
+
+/// `t1 + t2`, leaving out zeros.
+fn plus<'arena>(t1: Term<'arena>, t2: Term<'arena>, arena: &'arena Bump) -> Term<'arena> {
+    match (t1, t2) {
+        (Term::Constant(zero), term) | (term, Term::Constant(zero)) if zero.is_zero() => term,
+        (Term::Constant(c1), Term::Constant(c2)) => Term::Constant(c1 + c2),
+        (t1, t2) => Term::Sum(ArenaTerm::new_in(t1, arena), ArenaTerm::new_in(t2, arena)),
+    }
+}
+
+fn negated<'arena>(term: Term<'arena>, arena: &'arena Bump) -> Term<'arena> {
+    match term {
+        Term::Constant(c) => Term::Constant(-c),
+        Term::Negation(term) => *term,
+        term => Term::Negation(ArenaTerm::new_in(term, arena)),
+    }
+}
+
+/// `scale*x^(1/n)` for a nonnegative `x`, pulling `n`th power factors out of exact radicands.
+fn radical(x: Number, n: u32, scale: Number, arena: &Bump) -> Term<'_> {
+    use Term::*;
+
+    let root = x.pow(Number::ratio(1, n as i128).unwrap());
+
+    let (Number::Rational(numerator, d), false) = (x, root.is_exact()) else {
+        return Constant(scale * root);
+    };
+
+    /* (a/d)^(1/n) = (a d^(n-1))^(1/n)/d = k m^(1/n)/d */
+    let Some(mut m) = d.checked_pow(n - 1).and_then(|power| numerator.checked_mul(power)) else {
+        return Constant(scale * root);
+    };
+
+    let mut k = 1;
+    let mut factor: i128 = 2;
+
+    while factor <= 1_000_000 && factor.pow(n) <= m {
+        while m % factor.pow(n) == 0 {
+            m /= factor.pow(n);
+            k *= factor;
+        }
+
+        factor += 1;
+    }
+
+    let coefficient = scale * Number::ratio(k, d).unwrap();
+    let radical = Exponential(Number::Rational(m, 1), ArenaTerm::new_in(Constant(Number::ratio(1, n as i128).unwrap()), arena));
+
+    match coefficient {
+        _ if coefficient.is_one() => radical,
+        _ => Scale { coefficient, term: ArenaTerm::new_in(radical, arena) },
+    }
+}
+
+/// the coefficients scaled to integers, if they're all exact and that doesn't overflow.
+fn integer_coefficients(coefficients: &[Number]) -> Option<Vec<i128>> {
+    let denominators = coefficients.iter()
+        .map(|c| match c {
+            Number::Rational(_, d) => Some(*d),
+            Number::Float(_) => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    let lcm = denominators.iter().try_fold(1i128, |lcm, &d| (lcm / gcd(lcm, d)).checked_mul(d))?;
+
+    coefficients.iter()
+        .map(|c| match c {
+            Number::Rational(n, d) => n.checked_mul(lcm / d),
+            Number::Float(_) => None,
+        })
+        .collect()
+}
+
+/// divides every rational root `p/q` out of `coefficients` (lowest power first, no zero roots), with `p`
+/// dividing the constant coefficient and `q` the leading one. skipped when those are too large to factor.
+fn rational_roots(coefficients: &mut Vec<i128>) -> Vec<(Number, usize)> {
+    let mut roots = Vec::new();
+
+    let (Some(&constant), Some(&leading)) = (coefficients.first(), coefficients.last()) else {
+        return roots;
+    };
+
+    if coefficients.len() < 2 || constant.unsigned_abs() > 1_000_000_000_000 || leading.unsigned_abs() > 1_000_000_000_000 {
+        return roots;
+    }
+
+    for q in divisors(leading) {
+        for p in divisors(constant).into_iter().flat_map(|p| [p, -p]) {
+            if gcd(p, q) != 1 {
+                continue;
+            }
+
+            let mut multiplicity = 0;
+
+            while coefficients.len() > 1 && vanishes_at(coefficients, p, q) == Some(true) {
+                match deflated(coefficients, p, q) {
+                    Some(quotient) => *coefficients = quotient,
+                    None => break,
+                }
+
+                multiplicity += 1;
+            }
+
+            if multiplicity > 0 {
+                roots.push((Number::ratio(p, q).unwrap(), multiplicity));
+            }
+        }
+    }
+
+    roots
+}
+
+/// whether `p/q` is a root, by evaluating `q^n P(p/q)` exactly. `None` on overflow.
+fn vanishes_at(coefficients: &[i128], p: i128, q: i128) -> Option<bool> {
+    let n = coefficients.len() - 1;
+    let mut value = 0i128;
+
+    for (k, &c) in coefficients.iter().enumerate() {
+        let term = c.checked_mul(p.checked_pow(k as u32)?)?.checked_mul(q.checked_pow((n - k) as u32)?)?;
+        value = value.checked_add(term)?;
+    }
+
+    Some(value == 0)
+}
+
+/// `P(x) / (qx - p)` for a root `p/q` of `P`, whose coefficients stay integers by gauss's lemma.
+fn deflated(coefficients: &[i128], p: i128, q: i128) -> Option<Vec<i128>> {
+    let n = coefficients.len() - 1;
+    let mut quotient = vec![0i128; n];
+
+    quotient[n - 1] = coefficients[n] / q;
+    for k in (1..n).rev() {
+        quotient[k - 1] = coefficients[k].checked_add(p.checked_mul(quotient[k])?)? / q;
+    }
+
+    Some(quotient)
+}
+
+fn divisors(n: i128) -> Vec<i128> {
+    let n = n.abs();
+    let mut small = Vec::new();
+    let mut large = Vec::new();
+
+    let mut d = 1;
+    while d * d <= n {
+        if n % d == 0 {
+            small.push(d);
+            if d * d != n {
+                large.push(n / d);
+            }
+        }
+
+        d += 1;
+    }
+
+    small.extend(large.into_iter().rev());
+    small
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    match b {
+        0 => a.abs(),
+        _ => gcd(b, a % b),
+    }
+}
+
+/// the distinct complex roots of a real polynomial (lowest power first) with their multiplicities, largest real part
+/// first. degrees up to 4 go through the closed forms, higher ones through the aberth iteration, and either way the
+/// roots get polished.
+pub(crate) fn numeric_roots(p: &[f64]) -> Vec<(Complex, usize)> {
+    if p.len() < 2 {
+        return Vec::new();
+    }
+
+    let n = p.len() - 1;
+    let monic: Vec<f64> = p.iter().map(|c| c / p[n]).collect();
+
+    let roots = match n {
+        1 => vec![Complex::from(-monic[0])],
+        2 => quadratic(Complex::from(monic[1]), Complex::from(monic[0])).to_vec(),
+        3 => cubic(monic[2], monic[1], monic[0]).to_vec(),
+        4 => quartic(monic[3], monic[2], monic[1], monic[0]).to_vec(),
+        _ => aberth(p),
+    };
+
+    /* slowest decaying first */
+    let mut roots = cluster(p, roots);
+    roots.sort_by(|(z1, _), (z2, _)| z2.re.total_cmp(&z1.re).then(z2.im.total_cmp(&z1.im)));
+    roots
+}
+
+/// the roots of `x^2 + bx + c`, avoiding the cancellation in `-b ± sqrt(b^2 - 4c)`.
+fn quadratic(b: Complex, c: Complex) -> [Complex; 2] {
+    let two = Complex::from(2.0);
+    let mut d = (b * b - Complex::from(4.0) * c).sqrt();
+
+    if (b - d).abs() > (b + d).abs() {
+        d = -d;
+    }
+
+    let q = -(b + d) / two;
+
+    match q == Complex::ZERO {
+        true => [Complex::ZERO, Complex::ZERO],
+        false => [q, c / q],
+    }
+}
+
+/// the roots of `x^3 + ax^2 + bx + c` by cardano's formula on `y^3 + py + q`, with `x = y - a/3`.
+fn cubic(a: f64, b: f64, c: f64) -> [Complex; 3] {
+    let p = b - a * a / 3.0;
+    let q = 2.0 * a * a * a / 27.0 - a * b / 3.0 + c;
+
+    let root = Complex::from(q * q / 4.0 + p * p * p / 27.0).sqrt();
+    let w = match q > 0.0 {
+        true => Complex::from(-q / 2.0) - root,
+        false => Complex::from(-q / 2.0) + root,
+    };
+
+    let u = w.cbrt();
+    let omega = Complex::polar(1.0, 2.0 * std::f64::consts::PI / 3.0);
+    let shift = Complex::from(a / 3.0);
+
+    let mut roots = [Complex::ZERO; 3];
+    let mut rotated = u;
+
+    for root in &mut roots {
+        let y = match rotated == Complex::ZERO {
+            true => Complex::ZERO,
+            false => rotated - Complex::from(p / 3.0) / rotated,
+        };
+
+        *root = y - shift;
+        rotated = rotated * omega;
+    }
+
+    roots
+}
+
+/// the roots of `x^4 + ax^3 + bx^2 + cx + d` by ferrari's method on `y^4 + py^2 + qy + r`, with `x = y - a/4`.
+/// for `m` a root of the resolvent cubic, `(y^2 + p/2 + m)^2 = 2m (y - q/4m)^2`, which splits into two quadratics.
+fn quartic(a: f64, b: f64, c: f64, d: f64) -> [Complex; 4] {
+    let p = b - 3.0 * a * a / 8.0;
+    let q = c - a * b / 2.0 + a * a * a / 8.0;
+    let r = d - a * c / 4.0 + a * a * b / 16.0 - 3.0 * a * a * a * a / 256.0;
+    let shift = Complex::from(a / 4.0);
+
+    let ys = if q.abs() <= 1e-14 * (p.abs() + r.abs()).max(1.0) {
+        /* y^4 + py^2 + r is a quadratic in y^2 */
+        let [z1, z2] = quadratic(Complex::from(p), Complex::from(r));
+        [z1.sqrt(), -z1.sqrt(), z2.sqrt(), -z2.sqrt()]
+    } else {
+        let m = cubic(p, p * p / 4.0 - r, -q * q / 8.0).into_iter()
+            .max_by(|m1, m2| m1.abs().total_cmp(&m2.abs()))
+            .unwrap();
+
+        let s = (Complex::from(2.0) * m).sqrt();
+        let half_q = Complex::from(q) / (Complex::from(2.0) * s);
+        let constant = Complex::from(p / 2.0) + m;
+
+        let [y1, y2] = quadratic(-s, constant + half_q);
+        let [y3, y4] = quadratic(s, constant - half_q);
+        [y1, y2, y3, y4]
+    };
+
+    ys.map(|y| y - shift)
+}
+
+/// all complex roots of a real polynomial, by the aberth-ehrlich iteration.
+fn aberth(p: &[f64]) -> Vec<Complex> {
+    if p.len() < 2 {
+        return Vec::new();
+    }
+
+    let n = p.len() - 1;
+    let monic: Vec<Complex> = p.iter().map(|c| Complex::from(c / p[n])).collect();
+    let derivative: Vec<Complex> = (1..=n).map(|i| monic[i] * Complex::from(i as f64)).collect();
+
+    let eval = |q: &[Complex], z: Complex| q.iter().rev().fold(Complex::ZERO, |acc, &c| acc * z + c);
+
+    let radius = 1.0 + monic[..n].iter().fold(0.0f64, |m, c| m.max(c.abs()));
+    let mut roots: Vec<Complex> = (0..n)
+        .map(|k| Complex::polar(radius, 2.0 * std::f64::consts::PI * k as f64 / n as f64 + 0.4))
+        .collect();
+
+    for _ in 0..500 {
+        let mut largest_step = 0.0f64;
+
+        for k in 0..n {
+            let z = roots[k];
+            let value = eval(&monic, z);
+
+            if value == Complex::ZERO {
+                continue;
+            }
+
+            let ratio = value / eval(&derivative, z);
+            let repulsion = (0..n)
+                .filter(|&j| j != k)
+                .fold(Complex::ZERO, |acc, j| acc + Complex::ONE / (z - roots[j]));
+
+            let step = ratio / (Complex::ONE - ratio * repulsion);
+            roots[k] = z - step;
+            largest_step = largest_step.max(step.abs() / z.abs().max(1.0));
+        }
+
+        if largest_step < 1e-15 {
+            break;
+        }
+    }
+
+    roots
+}
+
+/// groups numerically repeated roots into `(root, multiplicity)`, then polishes each one with newton's method on
+/// the `(m-1)`th derivative of `p`, where an `m`-fold root is a simple one.
+fn cluster(p: &[f64], roots: Vec<Complex>) -> Vec<(Complex, usize)> {
+    let mut clusters: Vec<(Complex, Vec<Complex>)> = Vec::new();
+
+    for root in roots {
+        match clusters.iter_mut().find(|(center, _)| (*center - root).abs() < 1e-4 * center.abs().max(1.0)) {
+            Some((center, members)) => {
+                members.push(root);
+                let sum = members.iter().fold(Complex::ZERO, |acc, &m| acc + m);
+                *center = sum / Complex::from(members.len() as f64);
+            },
+            None => clusters.push((root, vec![root])),
+        }
+    }
+
+    let derivative = |q: &[Complex]| -> Vec<Complex> {
+        q.iter().enumerate().skip(1).map(|(i, &c)| c * Complex::from(i as f64)).collect()
+    };
+
+    let eval = |q: &[Complex], z: Complex| q.iter().rev().fold(Complex::ZERO, |acc, &c| acc * z + c);
+
+    clusters.into_iter().map(|(mut center, members)| {
+        let multiplicity = members.len();
+        let mut q: Vec<Complex> = p.iter().map(|&c| Complex::from(c)).collect();
+
+        for _ in 1..multiplicity {
+            q = derivative(&q);
+        }
+
+        let dq = derivative(&q);
+
+        for _ in 0..50 {
+            let slope = eval(&dq, center);
+
+            if slope == Complex::ZERO {
+                break;
+            }
+
+            let step = eval(&q, center) / slope;
+            center = center - step;
+
+            if step.abs() < 1e-16 * center.abs().max(1.0) {
+                break;
+            }
+        }
+
+        (Complex::new(tidy(center.re).to_f64(), tidy(center.im).to_f64()), multiplicity)
+    }).collect()
+}
@@ -1,247 +1,274 @@
-use crate::{Term, ArenaTerm};
+use crate::{Term, ArenaTerm, LadesError, Number};
+use crate::rewrite::{Rewrite, Rules, PrintObserver};
 use bumpalo::Bump;
+use std::sync::LazyLock;
 
-// consider adding SimplifyState enum with Deref<Target = Term> for cross-recursion communication
+pub(crate) static STANDARD_RULES: LazyLock<Rules> = LazyLock::new(Rules::default);
 
 impl<'arena> Term<'arena> {
-    pub fn simplify_in(self, arena: &'arena Bump) -> ArenaTerm<'arena> {
+    pub fn simplify_in(self, arena: &'arena Bump) -> Result<ArenaTerm<'arena>, LadesError> {
+        self.simplify_with(&STANDARD_RULES, &mut (), arena)
+    }
+
+    pub fn debug_simplify_in(self, arena: &'arena Bump) -> Result<Self, LadesError> {
+        Ok(*self.simplify_with(&STANDARD_RULES, &mut PrintObserver, arena)?)
+    }
+}
+
+/// the rules `simplify_in` uses.
+impl Default for Rules {
+    fn default() -> Self {
         use Term::*;
 
-        match self {
-            Constant(_) | Var(_) => ArenaTerm::new_in(self, &arena),
-
-            Sum(t1, t2) => match (*t1, *t2) {
-                (Constant(c1), Constant(c2)) => ArenaTerm::new_in(Constant(c1 + c2), &arena),
-
-                (t, Constant(0.0)) => ArenaTerm::new_in(t, &arena),
-                (Constant(0.0), t) => ArenaTerm::new_in(t, &arena),
-
-                (t1, t2) => {
-                    let t1 = t1.simplify_in(&arena);
-                    let t2 = t2.simplify_in(&arena);
-
-                    ArenaTerm::new_in(Sum(t1, t2), &arena)
-                }
-            },
-
-            Scale { coefficient, term } => match *term {
-                Constant(c) => ArenaTerm::new_in(Constant(coefficient * c), &arena),
-
-                Scale {
-                    coefficient: coefficient2,
-                    term: term2,
-                } => Scale { coefficient: coefficient * coefficient2, term: term2 }.simplify_in(&arena),
-
-                /* distributivity */
-                Sum(t1, t2) => Sum(
-                    Scale { coefficient: coefficient, term: t1.simplify_in(&arena) }.simplify_in(&arena),
-                    Scale { coefficient: coefficient, term: t2.simplify_in(&arena) }.simplify_in(&arena),
-                ).simplify_in(&arena),
-
-                term if matches!(
-                    term,
-                    Var(_) | Sin(_) | Cos(_) | Power { .. } | Exponential(_, _) | Derivative { .. }
-                ) => ArenaTerm::new_in(Scale { coefficient: coefficient, term: ArenaTerm::new_in(term, &arena) }, &arena),
-
-                _ => ArenaTerm::new_in(Scale { coefficient: coefficient, term: term.simplify_in(&arena) }, &arena),
-            },
-
-            Product(t1, t2) => match (*t1, *t2) {
-                (Constant(c1), Constant(c2)) => ArenaTerm::new_in(Constant(c1 * c2), &arena),
-
-                (_, Constant(0.0)) => ArenaTerm::new_in(Constant(0.0), &arena),
-                (Constant(0.0), _) => ArenaTerm::new_in(Constant(0.0), &arena),
-
-                (t, Constant(1.0)) => ArenaTerm::new_in(t, &arena),
-                (Constant(1.0), t) => ArenaTerm::new_in(t, &arena),
-
-                (t1, t2) => {
-                    let t1 = t1.simplify_in(&arena);
-                    let t2 = t2.simplify_in(&arena);
-                    ArenaTerm::new_in(Product(t1, t2), &arena)
-                }
-            },
-
-            Power { base, exponent } => match (*base, *exponent) {
-                (Constant(c1), Constant(c2)) => ArenaTerm::new_in(Constant(c1.powf(c2)), &arena),
-                (Constant(c), exponent) => ArenaTerm::new_in(Exponential(c, ArenaTerm::new_in(exponent, &arena)), &arena),
-
-                (base, exponent) => {
-                    let base = base.simplify_in(&arena);
-                    let exponent = exponent.simplify_in(&arena);
-
-                    ArenaTerm::new_in(Power {
-                        base: base,
-                        exponent: exponent,
-                    }, &arena)
-                }
-            },
-
-            Exponential(base, term) => match *term {
-                Constant(c) => ArenaTerm::new_in(Constant(base.powf(c)), &arena),
-                _ => {
-                    let term = term.simplify_in(&arena);
-                    ArenaTerm::new_in(Exponential(base, term), &arena)
-                }
-            },
-
-            Sin(term) => match *term {
-                Constant(c) => ArenaTerm::new_in(Constant(c.sin()), &arena),
-                term => ArenaTerm::new_in(Sin(term.simplify_in(&arena)), &arena),
-            },
-
-            Cos(term) => match *term {
-                Constant(c) => ArenaTerm::new_in(Constant(c.cos()), &arena),
-                _ => ArenaTerm::new_in(Cos(term.simplify_in(&arena)), &arena),
-            },
-
-            Derivative { order, wrt, term } => {
-                match *term.simplify_in(&arena) {
-                    Constant(c) => match order {
-                        0 => ArenaTerm::new_in(Constant(c), &arena),
-                        _ => ArenaTerm::new_in(Constant(0.0), &arena),
-                    },
+        let mut rules = Rules::empty();
 
-                    Var(_) => match (wrt, order) {
-                        (term, 0) => term,
-                        (_, 1) => ArenaTerm::new_in(Constant(1.0), &arena),
-                        (_, _) => ArenaTerm::new_in(Constant(0.0), &arena),
+        rules
+            .register(Rewrite::new("fold sum", |term, _| Ok(match term {
+                Sum(t1, t2) => match (&**t1, &**t2) {
+                    (Constant(c1), Constant(c2)) => Some(Constant(*c1 + *c2)),
+                    (t, Constant(c)) | (Constant(c), t) if c.is_zero() => Some(t.clone()),
+                    _ => None,
+                },
+                _ => None,
+            })))
+
+            .register(Rewrite::new("fold scale", |term, arena| Ok(match term {
+                Scale { coefficient, term } => match &**term {
+                    _ if coefficient.is_one() => Some((**term).clone()),
+                    Constant(c) => Some(Constant(*coefficient * *c)),
+
+                    Scale { coefficient: coefficient2, term } => Some(Scale {
+                        coefficient: *coefficient * *coefficient2,
+                        term: term.clone(),
+                    }),
+
+                    /* c(a/g) = (ca)/g */
+                    Quotient(numerator, denominator) => match **numerator {
+                        Constant(c) => Some(Quotient(ArenaTerm::new_in(Constant(*coefficient * c), arena), denominator.clone())),
+                        _ => None,
                     },
+                    Reciprocal(term) => Some(Quotient(ArenaTerm::new_in(Constant(*coefficient), arena), term.clone())),
 
-                    term => ArenaTerm::new_in(Derivative {
-                        order: order,
-                        wrt,
-                        term: ArenaTerm::new_in(term, &arena),
-                    }, &arena),
-                }
-            }
-        }
-    }
+                    /* distributivity */
+                    Sum(t1, t2) => Some(Sum(
+                        ArenaTerm::new_in(Scale { coefficient: *coefficient, term: t1.clone() }, arena),
+                        ArenaTerm::new_in(Scale { coefficient: *coefficient, term: t2.clone() }, arena),
+                    )),
+
+                    _ => None,
+                },
+                _ => None,
+            })))
+
+            .register(Rewrite::new("fold product", |term, arena| Ok(match term {
+                Product(t1, t2) => match (&**t1, &**t2) {
+                    (Constant(c1), Constant(c2)) => Some(Constant(*c1 * *c2)),
+                    (_, Constant(c)) | (Constant(c), _) if c.is_zero() => Some(Constant(Number::from(0))),
+                    (t, Constant(c)) | (Constant(c), t) if c.is_one() => Some(t.clone()),
+                    (t, Constant(c)) | (Constant(c), t) => Some(Scale { coefficient: *c, term: ArenaTerm::new_in(t.clone(), arena) }),
+                    _ => None,
+                },
+                _ => None,
+            })))
+
+            .register(Rewrite::new("fold imaginary", |term, _| Ok(match term {
+                /* i^2 = -1 */
+                Product(t1, t2) => match (&**t1, &**t2) {
+                    (I, I) => Some(Constant(Number::from(-1))),
+                    (Product(t, i), I) | (Product(i, t), I) | (I, Product(i, t)) | (I, Product(t, i)) if **i == I => Some(Negation(t.clone())),
+                    _ => None,
+                },
 
-    pub fn debug_simplify_in(self, arena: &'arena Bump) -> Self {
-        fn recursive_simplify<'arena>(__self: Term<'arena>, depth: usize, arena: &'arena Bump) -> Term<'arena> {
-            use Term::*;
+                Power { base, exponent } => match (&**base, &**exponent) {
+                    (I, Constant(n)) => n.to_integer().map(|n| match n.rem_euclid(4) {
+                        0 => Constant(Number::from(1)),
+                        1 => I,
+                        2 => Constant(Number::from(-1)),
+                        _ => Scale { coefficient: Number::from(-1), term: base.clone() },
+                    }),
+                    _ => None,
+                },
 
-            println!("{}<simplifying {}> {{", "   ".repeat(depth), __self);
+                /* 1/i = -i */
+                Reciprocal(term) if **term == I => Some(Scale { coefficient: Number::from(-1), term: term.clone() }),
 
-            let simplified = match __self {
-                Constant(_) | Var(_) => __self,
+                _ => None,
+            })))
 
-                Sum(t1, t2) => match (*t1, *t2) {
-                    (Constant(c1), Constant(c2)) => Constant(c1 + c2),
+            .register(Rewrite::new("fold quotient", |term, _| Ok(match term {
+                Quotient(t1, t2) => match (&**t1, &**t2) {
+                    (_, Constant(c)) if c.is_zero() => return Err(LadesError::DivisionByZero(term.to_string())),
+                    (Constant(c1), Constant(c2)) => Some(Constant(*c1 / *c2)),
+                    (Constant(c), _) if c.is_zero() => Some(Constant(Number::from(0))),
+                    (Constant(c), _) if c.is_one() => Some(Reciprocal(t2.clone())),
+                    (_, Constant(c)) => Some(Scale { coefficient: Number::from(1) / *c, term: t1.clone() }),
+                    _ => None,
+                },
 
-                    (t1, t2) => {
-                        let t1 = recursive_simplify(t1, depth + 1, &arena);
-                        let t2 = recursive_simplify(t2, depth + 1, &arena);
-                        Sum(ArenaTerm::new_in(t1, &arena), ArenaTerm::new_in(t2, &arena))
-                    }
+                Reciprocal(term) => match &**term {
+                    Constant(c) if c.is_zero() => return Err(LadesError::DivisionByZero(format!("1/{c}"))),
+                    Constant(c) => Some(Constant(Number::from(1) / *c)),
+                    Reciprocal(term) => Some((**term).clone()),
+                    _ => None,
                 },
 
-                Scale { coefficient, term } => match *term {
-                    Constant(c) => Constant(coefficient * c),
-                    Scale {
-                        coefficient: coefficient2,
-                        term: term2,
-                    } => recursive_simplify(Scale { coefficient: coefficient * coefficient2, term: term2 }, depth + 1, &arena),
+                Negation(term) => match &**term {
+                    Constant(c) => Some(Constant(-*c)),
+                    Negation(term) => Some((**term).clone()),
+                    Scale { coefficient, term } => Some(Scale { coefficient: -*coefficient, term: term.clone() }),
+                    _ => None,
+                },
 
-                    /* distributivity */
-                    Sum(t1, t2) => {
-                        let t1 = recursive_simplify(*t1, depth + 1, &arena);
-                        let t2 = recursive_simplify(*t2, depth + 1, &arena);
-
-                        let s1 = ArenaTerm::new_in(recursive_simplify(Scale {
-                            coefficient,
-                            term: ArenaTerm::new_in(t1, &arena),
-                        }, depth + 1, &arena), &arena);
-
-                        let s2 = ArenaTerm::new_in(recursive_simplify(Scale {
-                            coefficient,
-                            term: ArenaTerm::new_in(t2, &arena),
-                        }, depth + 1, &arena), &arena);
-
-                        recursive_simplify(Sum(s1, s2), depth + 1, &arena)
-                    }
-
-                    term if matches!(
-                        term,
-                        Var(_) | Sin(_) | Cos(_) | Power { .. } | Exponential(_, _) | Derivative { .. }
-                    ) => Scale { coefficient, term: ArenaTerm::new_in(term, &arena) },
-
-                    _ => Scale {
-                        coefficient,
-                        term: ArenaTerm::new_in(
-                            recursive_simplify(*term, depth + 1, &arena),
-                            &arena
-                        )
-                    },
+                _ => None,
+            })))
+
+            .register(Rewrite::new("fold power", |term, arena| Ok(match term {
+                Power { base, exponent } => match (&**base, &**exponent) {
+                    (Constant(c1), Constant(c2)) => Some(folded_powf(*c1, *c2)?.map_or_else(|| Exponential(*c1, exponent.clone()), Constant)),
+                    (base, Constant(c)) if c.is_one() => Some(base.clone()),
+                    (Constant(c), exponent) => Some(Exponential(*c, ArenaTerm::new_in(exponent.clone(), arena))),
+                    (E, exponent) => Some(Exponential(Number::E, ArenaTerm::new_in(exponent.clone(), arena))),
+                    _ => None,
+                },
+
+                Exponential(base, term) => match **term {
+                    Constant(c) if *base == Number::E && c.is_one() => Some(E),
+                    Constant(c) => folded_powf(*base, c)?.map(Constant),
+                    _ => None,
+                },
+
+                _ => None,
+            })))
+
+            .register(Rewrite::new("fold functions", |term, _| Ok(match term {
+                Sin(term) => match **term {
+                    Constant(c) => Some(Constant(c.sin())),
+                    _ => None,
                 },
 
-                Product(t1, t2) => match (*t1, *t2) {
-                    (Constant(c1), Constant(c2)) => Constant(c1 * c2),
+                Cos(term) => match **term {
+                    Constant(c) => Some(Constant(c.cos())),
+                    _ => None,
+                },
+
+                Ln(term) => match &**term {
+                    Constant(c) => Some(Constant(checked_ln(*c)?)),
+                    E => Some(Constant(Number::from(1))),
+
+                    /* ln(e^g) = g, ln(b^g) = ln(b) g */
+                    Exponential(base, term) if *base == Number::E => Some((**term).clone()),
+                    Exponential(base, term) => Some(Scale { coefficient: checked_ln(*base)?, term: term.clone() }),
 
-                    (t1, t2) => {
-                        let t1 = recursive_simplify(t1, depth + 1, &arena);
-                        let t2 = recursive_simplify(t2, depth + 1, &arena);
-                        Product(ArenaTerm::new_in(t1, &arena), ArenaTerm::new_in(t2, &arena))
-                    }
+                    _ => None,
                 },
 
-                Power { base, exponent } => match (*base, *exponent) {
-                    (Constant(c1), Constant(c2)) => Constant(c1.powf(c2)),
-                    (base, exponent) => {
-                        let base = recursive_simplify(base, depth + 1, &arena);
-                        let exponent = recursive_simplify(exponent, depth + 1, &arena);
-                        Power {
-                            base: ArenaTerm::new_in(base, &arena),
-                            exponent: ArenaTerm::new_in(exponent, &arena),
-                        }
-                    }
+                _ => None,
+            })))
+
+            .register(Rewrite::new("fold steps", |term, arena| Ok(match term {
+                Heaviside(term) => match &**term {
+                    Constant(c) => Some(Constant(Number::from(if c.is_negative() { 0 } else { 1 }))),
+
+                    /* u(cg) = u(g) for c > 0 */
+                    Scale { coefficient, term } if coefficient.is_positive() => Some(Heaviside(term.clone())),
+
+                    _ => None,
                 },
 
-                Exponential(base, term) => match *term {
-                    Constant(c) => Constant(base.powf(c)),
-                    _ => {
-                        let term = recursive_simplify(*term, depth + 1, &arena);
-                        Exponential(base, ArenaTerm::new_in(term, &arena))
-                    }
+                Dirac(term) => match &**term {
+                    Constant(c) if !c.is_zero() => Some(Constant(Number::from(0))),
+
+                    /* δ(cg) = δ(g)/|c| */
+                    Scale { coefficient, term } if !coefficient.is_zero() => Some(Scale {
+                        coefficient: Number::from(1) / coefficient.abs(),
+                        term: ArenaTerm::new_in(Dirac(term.clone()), arena),
+                    }),
+
+                    _ => None,
                 },
 
-                Sin(term) => match *term {
-                    Constant(c) => Constant(c.sin()),
-                    term => Sin(ArenaTerm::new_in(recursive_simplify(term, depth + 1, &arena), &arena)),
+                Piecewise { switch, below, above } => match &**switch {
+                    Constant(c) if c.is_negative() => Some((**below).clone()),
+                    Constant(_) => Some((**above).clone()),
+                    _ if below == above => Some((**below).clone()),
+                    _ => None,
                 },
 
-                Cos(term) => match *term {
-                    Constant(c) => Constant(c.cos()),
-                    _ => Cos(ArenaTerm::new_in(recursive_simplify(*term, depth + 1, &arena), &arena)),
+                _ => None,
+            })))
+
+            .register(Rewrite::new("fold derivative", |term, _| Ok(match term {
+                Derivative { order, wrt, term } => match &**term {
+                    Constant(c) => Some(Constant(if *order == 0 { *c } else { Number::from(0) })),
+                    term if *order == 0 => Some(term.clone()),
+
+                    /* any other variable is a function of `wrt` as far as we know, so it stays */
+                    Var(x) if **wrt == Var(*x) => Some(Constant(Number::from(if *order == 1 { 1 } else { 0 }))),
+
+                    _ => None,
+                },
+                _ => None,
+            })))
+
+            /* the canonical form is only taken when it merged something, so it doesn't reorder
+               everything or turn `a/b` into `ab^-1` where nothing was collected */
+            .register(Rewrite::new("collect like terms", |term, arena| Ok(match term {
+                Sum(..) | Product(..) => {
+                    let operands = match term {
+                        Sum(..) => summands,
+                        _ => factors,
+                    };
+
+                    let canonical = term.clone().canonicalize_in(arena)?;
+                    (operands(&canonical) < operands(term)).then_some(*canonical)
                 },
+                _ => None,
+            })));
 
-                Derivative { order, wrt, term } => {
-                    match recursive_simplify(*term, depth + 1, &arena) {
-                        Constant(c) => match order {
-                            0 => Constant(c),
-                            _ => Constant(0.0),
-                        },
+        rules
+    }
+}
+
+/// how many terms a sum has once nested sums are flattened.
+fn summands(term: &Term) -> usize {
+    match term {
+        Term::Sum(t1, t2) => summands(t1) + summands(t2),
+        _ => 1,
+    }
+}
+
+/// how many factors a product has once nested products are flattened, counting a coefficient.
+fn factors(term: &Term) -> usize {
+    match term {
+        Term::Product(t1, t2) => factors(t1) + factors(t2),
+        Term::Scale { term, .. } => 1 + factors(term),
+        _ => 1,
+    }
+}
 
-                        Var(_) => match (wrt, order) {
-                            (term, 0) => *term,
-                            (_, 1) => Constant(1.0),
-                            (_, _) => Constant(0.0),
-                        },
+/// `base^exponent`, refusing the cases that leave the reals.
+pub(crate) fn checked_powf(base: Number, exponent: Number) -> Result<Number, LadesError> {
+    match base {
+        _ if base.is_zero() && exponent.is_negative() => Err(LadesError::DivisionByZero(format!("{base}^{exponent}"))),
+        _ if base.is_negative() && exponent.to_integer().is_none() => Err(LadesError::Domain(format!("{base}^{exponent}"))),
+        _ => Ok(base.pow(exponent)),
+    }
+}
 
-                        term => Derivative {
-                            order,
-                            wrt,
-                            term: ArenaTerm::new_in(term, &arena),
-                        },
-                    }
-                }
-            };
+/// `checked_powf`, unless exact numbers give an inexact power: surds like `2^(1/2)` stay as they are, and so
+/// do powers of `e` other than `e^0`.
+pub(crate) fn folded_powf(base: Number, exponent: Number) -> Result<Option<Number>, LadesError> {
+    if base == Number::E {
+        return Ok(exponent.is_zero().then_some(Number::from(1)));
+    }
 
-            println!("{}}}", "   ".repeat(depth));
-            simplified
-        }
+    let power = checked_powf(base, exponent)?;
+    Ok((power.is_exact() || !base.is_exact() || !exponent.is_exact()).then_some(power))
+}
 
-        recursive_simplify(self, 0, arena)
+pub(crate) fn checked_ln(c: Number) -> Result<Number, LadesError> {
+    match c {
+        _ if !c.is_negative() && !c.is_zero() => Ok(c.ln()),
+        _ => Err(LadesError::Domain(format!("ln({c})"))),
     }
 }
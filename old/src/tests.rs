@@ -1,9 +1,8 @@
-#[allow(unused)]
-
+#[allow(unused, clippy::module_inception)]
 #[cfg(test)]
 mod tests {
     use crate::{Term, Term::*};
-    use crate::ArenaTerm;
+    use crate::{ArenaTerm, Number, Name};
     use bumpalo::Bump;
     use std::mem;
 
@@ -12,29 +11,29 @@ mod tests {
         let arena = Bump::with_capacity(64 * mem::size_of::<Term>());
 
         let fterm = Scale {
-            coefficient: 5.0,
+            coefficient: Number::from(5),
             term: ArenaTerm::new_in(Sum(
-                ArenaTerm::new_in(Constant(5.0), &arena),
-                ArenaTerm::new_in(Constant(10.0), &arena)
+                ArenaTerm::new_in(Constant(Number::from(5)), &arena),
+                ArenaTerm::new_in(Constant(Number::from(10)), &arena)
             ), &arena)
         };
 
         let dterm = Scale {
-            coefficient: 2.0,
+            coefficient: Number::from(2),
             term: ArenaTerm::new_in(Sum(
                 ArenaTerm::new_in(Scale {
-                    coefficient: 3.0,
+                    coefficient: Number::from(3),
                     term: ArenaTerm::new_in(Scale {
-                        coefficient: 7.0,
-                        term: ArenaTerm::new_in(Sin(ArenaTerm::new_in(Var('x'), &arena)), &arena) 
+                        coefficient: Number::from(7),
+                        term: ArenaTerm::new_in(Sin(ArenaTerm::new_in(Term::var('x').unwrap(), &arena)), &arena) 
                     }, &arena)
                 }, &arena),
 
                 ArenaTerm::new_in(Scale {
-                    coefficient: 3.0,
+                    coefficient: Number::from(3),
                     term: ArenaTerm::new_in(Scale {
-                        coefficient: 7.0,
-                        term: ArenaTerm::new_in(Cos(ArenaTerm::new_in(Var('x'), &arena)), &arena) 
+                        coefficient: Number::from(7),
+                        term: ArenaTerm::new_in(Cos(ArenaTerm::new_in(Term::var('x').unwrap(), &arena)), &arena) 
                     }, &arena)
                 }, &arena)
             ), &arena)
@@ -42,98 +41,1224 @@ mod tests {
 
         let terms = [
             Scale {
-                coefficient: 2.0,
+                coefficient: Number::from(2),
                 term: ArenaTerm::new_in(Scale {
-                    coefficient: 3.0,
+                    coefficient: Number::from(3),
                     term: ArenaTerm::new_in(Scale {
-                        coefficient: 2.0,
-                        term: ArenaTerm::new_in(Var('x'), &arena)
+                        coefficient: Number::from(2),
+                        term: ArenaTerm::new_in(Term::var('x').unwrap(), &arena)
                     }, &arena)
                 }, &arena)
             },
             
             Scale {
-                coefficient: 2.0,
+                coefficient: Number::from(2),
                 term: ArenaTerm::new_in(Scale {
-                    coefficient: 3.0,
+                    coefficient: Number::from(3),
                     term: ArenaTerm::new_in(Scale {
-                        coefficient: 7.0,
-                        term: ArenaTerm::new_in(Sin(ArenaTerm::new_in(Var('x'), &arena)), &arena)
+                        coefficient: Number::from(7),
+                        term: ArenaTerm::new_in(Sin(ArenaTerm::new_in(Term::var('x').unwrap(), &arena)), &arena)
                     }, &arena)
                 }, &arena)
             },
             
-            Derivative { order: 3, wrt: ArenaTerm::new_in(Var('x'), &arena), term: ArenaTerm::new_in(fterm, &arena) },
-            Derivative { order: 3, wrt: ArenaTerm::new_in(Var('x'), &arena), term: ArenaTerm::new_in(dterm, &arena) },
+            Derivative { order: 3, wrt: ArenaTerm::new_in(Term::var('x').unwrap(), &arena), term: ArenaTerm::new_in(fterm, &arena) },
+            Derivative { order: 3, wrt: ArenaTerm::new_in(Term::var('x').unwrap(), &arena), term: ArenaTerm::new_in(dterm, &arena) },
         ];
 
-        for t in terms { println!("= {}\n\n", t.debug_simplify_in(&arena) ); }
+        for t in terms { println!("= {}\n\n", t.debug_simplify_in(&arena).unwrap() ); }
 
-        let deriv = Derivative { order: 0, wrt: ArenaTerm::new_in(Var('t'), &arena), term: ArenaTerm::new_in(Scale {
-            coefficient: 5.0,
-            term: ArenaTerm::new_in(Sum(ArenaTerm::new_in(Constant(5.0), &arena), ArenaTerm::new_in(Constant(10.0), &arena)), &arena)
+        let deriv = Derivative { order: 0, wrt: ArenaTerm::new_in(Term::var('t').unwrap(), &arena), term: ArenaTerm::new_in(Scale {
+            coefficient: Number::from(5),
+            term: ArenaTerm::new_in(Sum(ArenaTerm::new_in(Constant(Number::from(5)), &arena), ArenaTerm::new_in(Constant(Number::from(10)), &arena)), &arena)
         }, &arena)};
 
         let deriv_sum = Sum(
             ArenaTerm::new_in(Scale {
-                coefficient: 2.0,
+                coefficient: Number::from(2),
                 term: ArenaTerm::new_in(Scale {
-                    coefficient: 3.0,
+                    coefficient: Number::from(3),
                     term: ArenaTerm::new_in(Scale {
-                        coefficient: 2.0,
-                        term: ArenaTerm::new_in(Var('x'), &arena)
+                        coefficient: Number::from(2),
+                        term: ArenaTerm::new_in(Term::var('x').unwrap(), &arena)
                     }, &arena)
                 }, &arena)
             }, &arena),
 
             ArenaTerm::new_in(Sum(
-                ArenaTerm::new_in(Constant(5.0), &arena),
-                ArenaTerm::new_in(Constant(10.0), &arena)
+                ArenaTerm::new_in(Constant(Number::from(5)), &arena),
+                ArenaTerm::new_in(Constant(Number::from(10)), &arena)
             ), &arena)
         );
 
         let deriv_product = Product(
-            ArenaTerm::new_in(Scale { coefficient: 3.0, term: ArenaTerm::new_in(Var('x'), &arena) }, &arena),
-            ArenaTerm::new_in(Var('b'), &arena)
+            ArenaTerm::new_in(Scale { coefficient: Number::from(3), term: ArenaTerm::new_in(Term::var('x').unwrap(), &arena) }, &arena),
+            ArenaTerm::new_in(Term::var('b').unwrap(), &arena)
         );
 
         let deriv_sin = Scale {
-            coefficient: 12.5,
+            coefficient: Number::ratio(25, 2).unwrap(),
             term: ArenaTerm::new_in(Sin(ArenaTerm::new_in(Sum(
                 ArenaTerm::new_in(Power {
-                    base: ArenaTerm::new_in(Var('x'), &arena),
-                    exponent: ArenaTerm::new_in(Constant(3.0), &arena)
+                    base: ArenaTerm::new_in(Term::var('x').unwrap(), &arena),
+                    exponent: ArenaTerm::new_in(Constant(Number::from(3)), &arena)
                 }, &arena),
-                ArenaTerm::new_in(Constant(-10.0), &arena)
+                ArenaTerm::new_in(Constant(Number::from(-10)), &arena)
             ), &arena)), &arena)
         };
 
         println!("{} -> {} =diff= {}",
             deriv,
-            deriv.clone().simplify_in(&arena),
-            deriv.clone().differentiate_in(Var('x'), &arena)
+            deriv.clone().simplify_in(&arena).unwrap(),
+            deriv.clone().differentiate_in(Term::var('x').unwrap(), &arena).unwrap()
         );
 
         println!("{} -> {} ~~> {}",
             deriv_sum,
-            deriv_sum.clone().simplify_in(&arena),
-            deriv_sum.clone().differentiate_in(Var('x'), &arena)
+            deriv_sum.clone().simplify_in(&arena).unwrap(),
+            deriv_sum.clone().differentiate_in(Term::var('x').unwrap(), &arena).unwrap()
         );
 
         println!("{} ~> {}",
             deriv_product.clone(),
-            deriv_product.differentiate_in(Var('x'), &arena)
+            deriv_product.differentiate_in(Term::var('x').unwrap(), &arena).unwrap()
         );
 
         println!("\n==============================");
         println!("  * size of term = {} bytes", mem::size_of::<Term>());
         println!("==============================");
 
-        /*println!("{} ~> {}",
+        println!("{} ~> {}",
             deriv_sin.clone(),
             deriv_sin
-                .differentiate_in(Var('x'), &arena)
-                .differentiate_in(Var('x'), &arena)
-        );*/
+                .differentiate_in(Term::var('x').unwrap(), &arena).unwrap()
+                .differentiate_in(Term::var('x').unwrap(), &arena).unwrap()
+        );
+    }
+
+    #[test]
+    fn laplace_transforms() {
+        use crate::LadesError;
+        use std::f64::consts::E;
+
+        let arena = Bump::new();
+        let t = || ArenaTerm::new_in(Term::var('t').unwrap(), &arena);
+        let scaled_t = |c| ArenaTerm::new_in(Scale { coefficient: c, term: t() }, &arena);
+
+        let transforms = [
+            (Constant(Number::from(3)), "3/s"),
+            (Power { base: t(), exponent: ArenaTerm::new_in(Constant(Number::from(3)), &arena) }, "6/s^4"),
+            (Exponential(Number::E, scaled_t(Number::from(2))), "1/(s + -2)"),
+            (Sin(scaled_t(Number::from(3))), "3/(s^2 + 9)"),
+            (Cos(scaled_t(Number::from(3))), "s/(s^2 + 9)"),
+        ];
+
+        for (term, expected) in transforms {
+            let transformed = term.laplace_in(Term::var('t').unwrap(), Term::var('s').unwrap(), &arena).unwrap();
+            assert_eq!(transformed.to_string(), expected);
+        }
+
+        /* powers of t times an exponential come out reduced, not as a derivative of 1/s */
+        let transformed = Term::parse_in("t^2 e^(3t)", &arena).unwrap().laplace_in(Term::var('t').unwrap(), Term::var('s').unwrap(), &arena).unwrap();
+        assert_eq!(transformed.to_string(), "2/(s + -3)^3");
+        assert_eq!(transformed.inverse_laplace_in(Term::var('s').unwrap(), Term::var('t').unwrap(), &arena).unwrap().to_string(), "t^2e^(3*t)");
+
+        let sin_t_squared = Sin(ArenaTerm::new_in(Power { base: t(), exponent: ArenaTerm::new_in(Constant(Number::from(2)), &arena) }, &arena));
+        assert!(matches!(sin_t_squared.laplace_in(Term::var('t').unwrap(), Term::var('s').unwrap(), &arena), Err(LadesError::Unsupported(_))));
+        assert!(matches!(Term::var('t').unwrap().laplace_in(Constant(Number::from(1)), Term::var('s').unwrap(), &arena), Err(LadesError::NonVariable(_))));
+        assert!(matches!(Exponential(Number::from(-2), t()).laplace_in(Term::var('t').unwrap(), Term::var('s').unwrap(), &arena), Err(LadesError::Domain(_))));
+    }
+
+    #[test]
+    fn inverse_laplace_transforms() {
+        let arena = Bump::new();
+        let s = || ArenaTerm::new_in(Term::var('s').unwrap(), &arena);
+        let c = |x| ArenaTerm::new_in(Constant(x), &arena);
+        let shifted_s = |a| ArenaTerm::new_in(Sum(s(), c(a)), &arena);
+
+        let distinct = Power {
+            base: ArenaTerm::new_in(Product(shifted_s(Number::from(1)), shifted_s(Number::from(2))), &arena),
+            exponent: c(Number::from(-1)),
+        };
+
+        let repeated = Power { base: shifted_s(Number::from(1)), exponent: c(Number::from(-3)) };
+
+        let complex_pair = Power {
+            base: ArenaTerm::new_in(Sum(ArenaTerm::new_in(Power { base: s(), exponent: c(Number::from(2)) }, &arena), c(Number::from(1))), &arena),
+            exponent: c(Number::from(-2)),
+        };
+
+        let mixed = Power {
+            base: ArenaTerm::new_in(Product(ArenaTerm::new_in(Power { base: s(), exponent: c(Number::from(2)) }, &arena), shifted_s(Number::from(3))), &arena),
+            exponent: c(Number::from(-1)),
+        };
+
+        let inverses = [
+            (distinct, "e^(-1*t) + -1*e^(-2*t)"),
+            (repeated, "0.5*t^2e^(-1*t)"),
+            (complex_pair, "0.5*sin(t) + -0.5*tcos(t)"),
+            (mixed, "-1/9 + 1/3*t + 1/9*e^(-3*t)"),
+        ];
+
+        for (term, expected) in inverses {
+            let inverted = term.inverse_laplace_in(Term::var('s').unwrap(), Term::var('t').unwrap(), &arena).unwrap();
+            assert_eq!(inverted.to_string(), expected);
+        }
+
+        let damped = Product(
+            ArenaTerm::new_in(Exponential(Number::E, ArenaTerm::new_in(Scale { coefficient: Number::from(-1), term: ArenaTerm::new_in(Term::var('t').unwrap(), &arena) }, &arena)), &arena),
+            ArenaTerm::new_in(Cos(ArenaTerm::new_in(Scale { coefficient: Number::from(2), term: ArenaTerm::new_in(Term::var('t').unwrap(), &arena) }, &arena)), &arena),
+        );
+
+        let round_trip = damped.clone()
+            .laplace_in(Term::var('t').unwrap(), Term::var('s').unwrap(), &arena).unwrap()
+            .inverse_laplace_in(Term::var('s').unwrap(), Term::var('t').unwrap(), &arena).unwrap();
+
+        assert!(*round_trip == damped);
+
+        /* rational and gaussian rational poles give exact coefficients, however large their denominators */
+        let inverse = |input| Term::parse_in(input, &arena).unwrap().inverse_laplace_in(Term::var('s').unwrap(), Term::var('t').unwrap(), &arena).unwrap().to_string();
+        assert_eq!(inverse("1/((s + 1)(s + 14))"), "1/13*e^(-1*t) + -1/13*e^(-14*t)");
+        assert_eq!(inverse("1/((s + 2)(s^2 + 2s + 10))"), "0.1*e^(-2*t) + -0.1*e^(-1*t)cos(3*t) + 1/30*e^(-1*t)sin(3*t)");
+    }
+
+    #[test]
+    fn solve_ivp_linear_odes() {
+        use crate::{Equation, LadesError};
+        use std::collections::HashMap;
+
+        let arena = Bump::new();
+        let y = || ArenaTerm::new_in(Term::var('y').unwrap(), &arena);
+        let t = || ArenaTerm::new_in(Term::var('t').unwrap(), &arena);
+        let d = |order| Derivative { order, wrt: t(), term: y() };
+
+        /* y'' + 3y' + 2y = 0 */
+        let overdamped = Equation::new(vec![
+            d(2),
+            Scale { coefficient: Number::from(3), term: ArenaTerm::new_in(d(1), &arena) },
+            Scale { coefficient: Number::from(2), term: y() },
+        ], vec![]);
+
+        let initial = HashMap::from([(0, Number::from(1)), (1, Number::from(0))]);
+        let solution = overdamped.solve_ivp(Term::var('y').unwrap(), Term::var('t').unwrap(), &initial, &arena).unwrap();
+        assert_eq!(solution.to_string(), "2*e^(-1*t) + -1*e^(-2*t)");
+
+        /* y' + 2y = e^-t */
+        let forced = Equation::new(
+            vec![d(1), Scale { coefficient: Number::from(2), term: y() }],
+            vec![Exponential(Number::E, ArenaTerm::new_in(Scale { coefficient: Number::from(-1), term: t() }, &arena))],
+        );
+
+        let solution = forced.solve_ivp(Term::var('y').unwrap(), Term::var('t').unwrap(), &HashMap::from([(0, Number::from(0))]), &arena).unwrap();
+        assert_eq!(solution.to_string(), "e^(-1*t) + -1*e^(-2*t)");
+
+        /* initial conditions are numbers, so a third stays a third instead of 0.333... */
+        let initial = HashMap::from([(0, Number::ratio(1, 3).unwrap())]);
+        let solution = forced.solve_ivp(Term::var('y').unwrap(), Term::var('t').unwrap(), &initial, &arena).unwrap();
+        assert_eq!(solution.to_string(), "e^(-1*t) + -2/3*e^(-2*t)");
+
+        /* y'' = -y */
+        let oscillator = Equation::new(vec![d(2)], vec![Scale { coefficient: Number::from(-1), term: y() }]);
+
+        let solution = oscillator.solve_ivp(Term::var('y').unwrap(), Term::var('t').unwrap(), &HashMap::from([(0, Number::from(0)), (1, Number::from(1))]), &arena).unwrap();
+        assert_eq!(solution.to_string(), "sin(t)");
+
+        assert!(matches!(
+            oscillator.solve_ivp(Term::var('y').unwrap(), Term::var('t').unwrap(), &HashMap::from([(0, Number::from(0))]), &arena),
+            Err(LadesError::MissingInitialCondition(y, 1)) if y == "y"
+        ));
+
+        let nonlinear = Equation::new(vec![d(1), Product(y(), y())], vec![]);
+        assert!(matches!(
+            nonlinear.solve_ivp(Term::var('y').unwrap(), Term::var('t').unwrap(), &initial, &arena),
+            Err(LadesError::NonLinear(_))
+        ));
+    }
+
+    #[test]
+    fn solve_ivp_systems() {
+        use crate::{Equation, System, LadesError};
+        use std::collections::HashMap;
+
+        let arena = Bump::new();
+        let var = |c| ArenaTerm::new_in(Term::var(c).unwrap(), &arena);
+        let d = |c| Derivative { order: 1, wrt: var('t'), term: var(c) };
+        let scaled = |coefficient, c| Scale { coefficient, term: var(c) };
+
+        /* x' = y, y' = -x */
+        let rotation = System::new(vec![
+            Equation::new(vec![d('x')], vec![Term::var('y').unwrap()]),
+            Equation::new(vec![d('y')], vec![scaled(Number::from(-1), 'x')]),
+        ]);
+
+        let initial = HashMap::from([(("x", 0), Number::from(0)), (("y", 0), Number::from(1))]);
+        let solutions = rotation.solve_ivp(&[Term::var('x').unwrap(), Term::var('y').unwrap()], Term::var('t').unwrap(), &initial, &arena).unwrap();
+        assert_eq!(solutions[0].to_string(), "sin(t)");
+        assert_eq!(solutions[1].to_string(), "cos(t)");
+
+        /* exact initial values give exact coefficients */
+        let initial = HashMap::from([(("x", 0), Number::ratio(1, 3).unwrap()), (("y", 0), Number::from(0))]);
+        let solutions = rotation.solve_ivp(&[Term::var('x').unwrap(), Term::var('y').unwrap()], Term::var('t').unwrap(), &initial, &arena).unwrap();
+        assert_eq!(solutions[0].to_string(), "1/3*cos(t)");
+        assert_eq!(solutions[1].to_string(), "-1/3*sin(t)");
+
+        /* x' = -2x + y, y' = x - 2y */
+        let coupled = System::new(vec![
+            Equation::new(vec![d('x')], vec![scaled(Number::from(-2), 'x'), Term::var('y').unwrap()]),
+            Equation::new(vec![d('y')], vec![Term::var('x').unwrap(), scaled(Number::from(-2), 'y')]),
+        ]);
+
+        let initial = HashMap::from([(("x", 0), Number::from(1)), (("y", 0), Number::from(0))]);
+        let solutions = coupled.solve_ivp(&[Term::var('x').unwrap(), Term::var('y').unwrap()], Term::var('t').unwrap(), &initial, &arena).unwrap();
+        assert_eq!(solutions[0].to_string(), "0.5*e^(-1*t) + 0.5*e^(-3*t)");
+        assert_eq!(solutions[1].to_string(), "0.5*e^(-1*t) + -0.5*e^(-3*t)");
+
+        let dependent = System::new(vec![
+            Equation::new(vec![d('x'), d('y')], vec![]),
+            Equation::new(vec![scaled(Number::from(2), 'x'), scaled(Number::from(2), 'y')], vec![Constant(Number::from(1))]),
+        ]);
+
+        assert!(matches!(
+            dependent.solve_ivp(&[Term::var('x').unwrap(), Term::var('y').unwrap()], Term::var('t').unwrap(), &initial, &arena),
+            Err(LadesError::Singular)
+        ));
+
+        /* nothing to solve for, or nothing to solve with */
+        let empty = System::new(vec![]);
+        assert!(matches!(empty.solve_ivp(&[], Term::var('t').unwrap(), &HashMap::new(), &arena), Err(LadesError::Singular)));
+        assert!(matches!(empty.solve_ivp(&[Term::var('x').unwrap()], Term::var('t').unwrap(), &initial, &arena), Err(LadesError::Singular)));
+        assert_eq!(crate::equation::determinant(&[], &arena).to_string(), "1");
+    }
+
+    #[test]
+    fn logarithms_and_general_powers() {
+        use std::f64::consts::E;
+
+        let arena = Bump::new();
+        let x = || ArenaTerm::new_in(Term::var('x').unwrap(), &arena);
+
+        /* d/dx x^x = x^x (ln(x) + x/x) */
+        let x_to_the_x = Power { base: x(), exponent: x() };
+        assert_eq!(x_to_the_x.differentiate_in(Term::var('x').unwrap(), &arena).unwrap().to_string(), "x^x(ln(x) + 1)");
+
+        let two_to_the_x = Exponential(Number::from(2), x());
+        assert_eq!(two_to_the_x.differentiate_in(Term::var('x').unwrap(), &arena).unwrap().to_string(), "0.6931471805599453*2^x");
+
+        let ln_sin_x = Ln(ArenaTerm::new_in(Sin(x()), &arena));
+        assert_eq!(ln_sin_x.differentiate_in(Term::var('x').unwrap(), &arena).unwrap().to_string(), "cos(x)sin(x)^-1");
+
+        let ln_e_cubed = Ln(ArenaTerm::new_in(Exponential(Number::E, ArenaTerm::new_in(Constant(Number::from(3)), &arena)), &arena));
+        assert_eq!(ln_e_cubed.simplify_in(&arena).unwrap().to_string(), "3");
+    }
+
+    #[test]
+    fn differentiation_covers_every_term() {
+        let arena = Bump::new();
+        let var = |c| ArenaTerm::new_in(Term::var(c).unwrap(), &arena);
+
+        let cos_x_squared = Cos(ArenaTerm::new_in(Power { base: var('x'), exponent: ArenaTerm::new_in(Constant(Number::from(2)), &arena) }, &arena));
+        let exp_3x = Exponential(Number::E, ArenaTerm::new_in(Scale { coefficient: Number::from(3), term: var('x') }, &arena));
+        let y_prime = Derivative { order: 1, wrt: var('t'), term: var('y') };
+        let xy_prime = Derivative { order: 1, wrt: var('t'), term: ArenaTerm::new_in(Product(var('x'), var('y')), &arena) };
+
+        let derivatives = [
+            (cos_x_squared, 'x', "-1*sin(x^2)(2*x)"),
+            (exp_3x, 'x', "3*e^(3*x)"),
+            (y_prime.clone(), 't', "(y, wrt: t)''"),
+            (y_prime, 'x', "0"),
+            (xy_prime, 'x', "(y, wrt: t)'"),
+        ];
+
+        for (term, wrt, expected) in derivatives {
+            assert_eq!(term.differentiate_in(Term::var(wrt).unwrap(), &arena).unwrap().to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn errors_instead_of_panics() {
+        use crate::LadesError;
+
+        let arena = Bump::new();
+        let c = |x| ArenaTerm::new_in(Constant(x), &arena);
+
+        assert!(matches!(
+            Term::var('x').unwrap().differentiate_in(Constant(Number::from(2)), &arena),
+            Err(LadesError::NonVariable(_))
+        ));
+
+        assert!(matches!(Ln(c(Number::from(-1))).simplify_in(&arena), Err(LadesError::Domain(_))));
+        assert!(matches!(Power { base: c(Number::from(0)), exponent: c(Number::from(-1)) }.simplify_in(&arena), Err(LadesError::DivisionByZero(_))));
+
+        /* errors deep inside a term still come out of differentiation */
+        let nested = Sin(ArenaTerm::new_in(Sum(
+            ArenaTerm::new_in(Term::var('x').unwrap(), &arena),
+            ArenaTerm::new_in(Power { base: c(Number::from(-8)), exponent: c(Number::ratio(1, 2).unwrap()) }, &arena),
+        ), &arena));
+
+        assert!(matches!(nested.differentiate_in(Term::var('x').unwrap(), &arena), Err(LadesError::Domain(_))));
+
+        /* b^x with b <= 0 has no logarithm to differentiate or take apart with */
+        let negative_base = || Exponential(Number::from(-2), ArenaTerm::new_in(Term::var('x').unwrap(), &arena));
+        assert!(matches!(negative_base().differentiate_in(Term::var('x').unwrap(), &arena), Err(LadesError::Domain(_))));
+        assert!(matches!(Ln(ArenaTerm::new_in(negative_base(), &arena)).simplify_in(&arena), Err(LadesError::Domain(_))));
+    }
+
+    #[test]
+    fn parse_round_trips() {
+        use crate::{ParseError, ParseErrorKind};
+
+        let arena = Bump::new();
+        let parse = |input| Term::parse_in(input, &arena);
+
+        let parsed = parse("3*sin(x^2) + e^(2t)").unwrap();
+        assert_eq!(parsed.to_string(), "3*sin(x^2) + e^(2t)");
+        assert_eq!(parse("2sin(t) - x/4").unwrap().to_string(), "2sin(t) - x/4");
+        assert_eq!(parse("exp(-t) ln(x)").unwrap().to_string(), "e^(-t)ln(x)");
+
+        /* both derivative notations, and the one `Display` uses */
+        assert_eq!(parse("y''").unwrap().to_string(), "(y, wrt: t)''");
+        assert_eq!(parse("d^2y/dt^2 + dx/ds").unwrap().to_string(), "(y, wrt: t)'' + (x, wrt: s)'");
+        assert!(*parse("(y, wrt: t)''").unwrap() == *parse("y''").unwrap());
+
+        assert_eq!(parse("(x + 1").err(), Some(ParseError { kind: ParseErrorKind::Unclosed, span: 0..1 }));
+        assert_eq!(parse("2x + * y").err(), Some(ParseError { kind: ParseErrorKind::UnexpectedChar('*'), span: 5..6 }));
+        assert_eq!(parse("d^2y/dt").err(), Some(ParseError { kind: ParseErrorKind::MismatchedOrder, span: 0..7 }));
+        assert_eq!(parse("sin(x) +").err().map(|e| e.kind), Some(ParseErrorKind::UnexpectedEnd));
+
+        /* whatever gets printed reads back into the same tree */
+        let terms = [
+            "x^x(ln(x) + xx^-1)",
+            "-1*sin(x^2)(2*x)",
+            "0.5*t^2e^(-1*t)",
+            "3*(s^2 + 9)^-1 + (-2)^(x + y)",
+            "(2*x)y + x(yz) + (x^y)^z + x^y^z",
+            "a + (b + c) + -0.25*cos(2t + 1)",
+            "x(2) + (y, wrt: x)'(1.5)",
+            "(s + 1)/(s^2 + 4) - 1/s",
+            "-x^2 - (a - b) + x/2/y",
+            "(a/b)c + a(b/c) + (x/y)^2",
+        ];
+
+        for input in terms {
+            let term = parse(input).unwrap();
+            assert_eq!(term.to_string(), input);
+            assert!(*Term::parse_in(&term.to_string(), &arena).unwrap() == *term);
+        }
+
+        /* letters that spell a function only call it when they were parsed as one */
+        let var = |c| ArenaTerm::new_in(Term::var(c).unwrap(), &arena);
+        let sum = ArenaTerm::new_in(Sum(var('x'), ArenaTerm::new_in(Constant(Number::from(1)), &arena)), &arena);
+
+        for (first, second) in [('l', 'n'), ('x', 'p')] {
+            let product = Product(ArenaTerm::new_in(Product(var(first), var(second)), &arena), sum.clone());
+            assert!(*Term::parse_in(&product.to_string(), &arena).unwrap() == product, "{product}");
+        }
+
+        let product = Product(ArenaTerm::new_in(Product(var('l'), var('n')), &arena), sum);
+        assert_eq!(product.to_string(), "ln*(x + 1)");
+        assert_eq!(parse("a sin(x)(x + 1)").unwrap().to_string(), "asin(x)(x + 1)");
+    }
+
+    #[test]
+    fn builder_reads_like_math() {
+        use crate::{Context, IntoExpr};
+
+        let arena = Bump::new();
+        let ctx = Context::new(&arena);
+        let (x, t, y) = (ctx.var('x').unwrap(), ctx.var('t').unwrap(), ctx.var('y').unwrap());
+
+        assert_eq!((3.0 * ctx.sin(x.pow(2)) + ctx.exp(2 * t)).to_string(), "3*sin(x^2) + e^(2*t)");
+        assert_eq!((x - y / 4).to_string(), "x - y/4");
+        assert_eq!((1 - x / y).to_string(), "1 - x/y");
+        assert_eq!((-ctx.ln(x) * ctx.cos(t)).to_string(), "-ln(x)cos(t)");
+        assert_eq!(2.0.into_expr(ctx).pow(x).to_string(), "2^x");
+
+        /* expressions are copies, so reusing one doesn't move it */
+        let wrt = Name::letter('t').unwrap();
+        let ode = y.derivative(wrt, 2) + 3 * y.derivative(wrt, 1) + 2 * y;
+        assert_eq!(ode.to_string(), "(y, wrt: t)'' + 3*(y, wrt: t)' + 2*y");
+        assert!(*ode.as_term() == *Term::parse_in("y'' + 3*y' + 2*y", &arena).unwrap());
+
+        let derivative = (x * ctx.sin(x)).boxed().differentiate_in(Term::var('x').unwrap(), &arena).unwrap();
+        assert_eq!(derivative.to_string(), (ctx.sin(x) + x * ctx.cos(x)).to_string());
+    }
+
+    #[test]
+    fn numeric_evaluation() {
+        use crate::EvalError;
+
+        let arena = Bump::new();
+        let parse = |input| Term::parse_in(input, &arena).unwrap();
+        let no_vars = |_: Name| None;
+
+        assert_eq!(parse("3*sin(x^2) + e^(2t)").eval(&|v| match v.as_str() { "x" => Some(0.0), "t" => Some(0.5), _ => None }), Ok(std::f64::consts::E));
+        assert_eq!(parse("2^-1 + ln(1) + (y, wrt: t)").eval(&|_| Some(1.5)), Ok(2.0));
+
+        assert_eq!(parse("x + y").eval(&|v| (v == Name::letter('x').unwrap()).then_some(1.0)), Err(EvalError::Unbound("y".to_string())));
+        assert!(matches!(parse("ln(0)").eval(&no_vars), Err(EvalError::Domain(_))));
+        assert!(matches!(parse("x^-1").eval(&|_| Some(0.0)), Err(EvalError::DivisionByZero(_))));
+        assert!(matches!(parse("(-8)^(1/3)").eval(&no_vars), Err(EvalError::Domain(_))));
+        assert!(matches!(parse("y'").eval(&|_| Some(1.0)), Err(EvalError::Derivative(_))));
+
+        /* the solution of y'' + 3y' + 2y = 0 against its closed form */
+        let solution = parse("2*e^(-1*t) + -1*e^(-2*t)");
+        let points = [0.0, 0.5, 1.0, 4.0];
+        let values = solution.eval_over(Name::letter('t').unwrap(), &points, &no_vars).unwrap();
+
+        for (t, value) in points.iter().zip(values) {
+            assert!((value - (2.0 * (-t).exp() - (-2.0 * t).exp())).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn canonical_forms() {
+        let arena = Bump::new();
+        let canonical = |input| Term::parse_in(input, &arena).unwrap().canonicalize_in(&arena).unwrap();
+
+        let cases = [
+            ("x + x", "2*x"),
+            ("2x + 3x", "5*x"),
+            ("5 + (x + 3)", "x + 8"),
+            ("x*x", "x^2"),
+            ("x^2 x^-1 y^0", "x"),
+            ("x + y - x", "y"),
+            ("3*(x + 2) - 6", "3*x"),
+            ("(2x)^2 + x^2/2", "4.5*x^2"),
+            ("x^a x^b", "x^(a + b)"),
+            ("e^t e^(2t) - e^(3t)", "0"),
+            ("2^3 sin(0) + ln(e^2) + (y, wrt: t)'' y'", "y'' y' + 2"),
+        ];
+
+        for (input, expected) in cases {
+            assert!(*canonical(input) == *canonical(expected), "{input} isn't {expected}");
+        }
+
+        assert_eq!(canonical("y + 2x + xy + 1 + x^2").to_string(), "2*x + x^2 + y + xy + 1");
+
+        /* operand order doesn't matter once canonical */
+        let pairs = [
+            ("x*y + 2*x", "2x + yx"),
+            ("sin(t)cos(t) + 1", "1 + cos(t)sin(t)"),
+            ("(x + 1)(x - 1)", "(-1 + x)(1 + x)"),
+            ("x^(1 + t) y", "y x^(t + 1)"),
+        ];
+
+        for (a, b) in pairs {
+            assert!(*canonical(a) == *canonical(b), "{a} and {b} differ");
+        }
+    }
+
+    #[test]
+    fn rewrite_rules() {
+        use crate::{LadesError, Rewrite, Rules, SimplifyObserver};
+
+        let arena = Bump::new();
+        let parse = |input| *Term::parse_in(input, &arena).unwrap();
+
+        /* a single pass used to leave (1 + 2) + 3 at 3 + 3 */
+        assert_eq!(parse("(1 + 2) + 3*(x + 0)").simplify_in(&arena).unwrap().to_string(), "3 + 3*x");
+
+        /* like terms and constants are collected across the whole sum or product */
+        let simplify = |input| parse(input).simplify_in(&arena).unwrap().to_string();
+        assert_eq!(simplify("x + x"), "2*x");
+        assert_eq!(simplify("2x + 3x"), "5*x");
+        assert_eq!(simplify("5 + (x + 3)"), "x + 8");
+        assert_eq!(simplify("x*x"), "x^2");
+        assert_eq!(simplify("x + y"), "x + y");
+
+        /* callers can add their own rules */
+        let mut rules = Rules::default();
+        rules.register(Rewrite::new("ln(x^n) = n ln(x)", |term, arena| Ok(match term {
+            Ln(inner) => match &**inner {
+                Power { base, exponent } => match **exponent {
+                    Constant(n) => Some(Scale { coefficient: n, term: ArenaTerm::new_in(Ln(base.clone()), arena) }),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        })));
+
+        #[derive(Default)]
+        struct Applied(Vec<String>);
+
+        impl<'arena> SimplifyObserver<'arena> for Applied {
+            fn rewrite(&mut self, rule: &str, _before: &Term<'arena>, _after: &Term<'arena>, _depth: usize) {
+                self.0.push(rule.to_string());
+            }
+        }
+
+        let mut applied = Applied::default();
+        let simplified = parse("2*ln(x^3) + 0").simplify_with(&rules, &mut applied, &arena).unwrap();
+
+        assert_eq!(simplified.to_string(), "6*ln(x)");
+        assert_eq!(applied.0, ["ln(x^n) = n ln(x)", "fold scale", "fold sum"]);
+
+        assert!(rules.remove("fold sum").is_some() && rules.remove("collect like terms").is_some());
+        assert!(!rules.names().any(|name| name == "fold sum"));
+        assert_eq!(parse("1 + 2").simplify_with(&rules, &mut (), &arena).unwrap().to_string(), "1 + 2");
+
+        /* rules that never settle stop at the iteration limit with an error, not with whatever they got to */
+        let mut swap = Rules::empty();
+        swap.max_iterations = 3;
+        swap.register(Rewrite::new("swap", |term, _| Ok(match term {
+            Sum(t1, t2) => Some(Sum(t2.clone(), t1.clone())),
+            _ => None,
+        })));
+
+        assert_eq!(parse("x + y").simplify_with(&swap, &mut (), &arena).map(|t| t.to_string()), Err(LadesError::NotConverged("y + x".to_string())));
+
+        let mut oscillating = Rules::empty();
+        oscillating.register(Rewrite::from_patterns("sin to cos", "sin(?a)", "cos(?a)").unwrap());
+        oscillating.register(Rewrite::from_patterns("cos to sin", "cos(?a)", "sin(?a)").unwrap());
+
+        let mut applied = Applied::default();
+        let result = parse("sin(x)").simplify_with(&oscillating, &mut applied, &arena);
+        assert!(matches!(result, Err(LadesError::NotConverged(_))));
+        assert_eq!(applied.0.len(), oscillating.max_iterations);
+        assert_eq!(applied.0[..2], ["sin to cos", "cos to sin"]);
+    }
+
+    #[test]
+    fn simplify_traces() {
+        let arena = Bump::new();
+        let term = *Term::parse_in("2*(x + 3*4) + 0", &arena).unwrap();
+
+        let (simplified, trace) = term.simplify_traced_in(&arena).unwrap();
+        assert_eq!(simplified.to_string(), "2*x + 24");
+
+        let steps: Vec<_> = trace.steps.iter()
+            .map(|step| (step.rule.as_str(), step.before.to_string(), step.after.to_string(), step.depth))
+            .collect();
+
+        assert_eq!(steps, [
+            ("fold scale", "3*4".to_string(), "12".to_string(), 3),
+            ("fold scale", "2*(x + 12)".to_string(), "2*x + 2*12".to_string(), 1),
+            ("fold sum", "2*x + 2*12 + 0".to_string(), "2*x + 2*12".to_string(), 0),
+            ("fold scale", "2*12".to_string(), "24".to_string(), 1),
+        ]);
+
+        assert_eq!(trace.derivation(), "\
+2*(x + 3*4) + 0
+= 2*(x + 12) + 0    (fold scale: 3*4 => 12)
+= 2*x + 2*12 + 0    (fold scale: 2*(x + 12) => 2*x + 2*12)
+= 2*x + 2*12    (fold sum: 2*x + 2*12 + 0 => 2*x + 2*12)
+= 2*x + 24    (fold scale: 2*12 => 24)
+");
+
+        assert!(trace.to_json().starts_with(r#"{"input": "2*(x + 3*4) + 0", "steps": [{"rule": "fold scale", "before": "3*4", "after": "12", "depth": 3, "path": [0, 0, 1]}, "#));
+        assert_eq!(trace.to_string().lines().nth(1), Some("   2*(x + 12) => 2*x + 2*12    [fold scale]"));
+
+        /* nothing to do, nothing traced */
+        let (_, trace) = Term::var('x').unwrap().simplify_traced_in(&arena).unwrap();
+        assert!(trace.steps.is_empty());
+        assert_eq!(trace.derivation(), "x\n");
+    }
+
+    #[test]
+    fn substitution_and_patterns() {
+        use crate::{Pattern, Rewrite, Rules, ParseErrorKind};
+
+        let arena = Bump::new();
+        let parse = |input| Term::parse_in(input, &arena).unwrap();
+
+        /* changing variables, then applying an initial condition */
+        let term = parse("sin(t) + t^2 (y, wrt: t)'");
+        let shifted = term.substitute_in(Name::letter('t').unwrap(), &parse("t + 1"), &arena);
+        assert_eq!(shifted.to_string(), "sin(t + 1) + (t + 1)^2(y, wrt: t)'");
+        assert_eq!(parse("3x + e^x").substitute_in(Name::letter('x').unwrap(), &Constant(Number::from(0)), &arena).simplify_in(&arena).unwrap().to_string(), "1");
+
+        let pythagoras = Pattern::parse_in("sin(?a)^2 + cos(?a)^2", &arena).unwrap();
+        assert_eq!(pythagoras.wildcards, ["a"]);
+        assert_eq!(Pattern::parse_in("?a + ?b + ?a", &arena).unwrap().wildcards, ["a", "b"]);
+
+        /* `e` and `i` are constants, so `?e` and `?i` can't be wildcards */
+        let error = Pattern::parse_in("?a + ?i", &arena).err().unwrap();
+        assert_eq!((error.kind, error.span), (ParseErrorKind::ReservedName("i".to_string()), 5..7));
+        assert!(Rewrite::from_patterns("constants", "?e + ?b", "?b").is_err());
+
+        /* renaming a variable renames what derivatives are taken with respect to, anything else leaves it be */
+        let renamed = parse("(y, wrt: t)'").substitute_in(Name::letter('t').unwrap(), &parse("u"), &arena);
+        assert_eq!(renamed.to_string(), "(y, wrt: u)'");
+
+        let bindings = pythagoras.matches(&parse("cos(2t + 1)^2 + sin(2t + 1)^2")).unwrap();
+        assert_eq!(bindings["a"].to_string(), "2t + 1");
+
+        assert!(pythagoras.matches(&parse("sin(x)^2 + cos(y)^2")).is_none());
+        assert!(pythagoras.matches(&parse("sin(x)^3 + cos(x)^2")).is_none());
+
+        /* non-wildcard variables only match themselves */
+        let pattern = Pattern::parse_in("?f x", &arena).unwrap();
+        assert_eq!(pattern.matches(&parse("x ln(x)")).unwrap()["f"].to_string(), "ln(x)");
+        assert!(pattern.matches(&parse("y ln(x)")).is_none());
+
+        let replacement = Pattern::parse_in("2*?f", &arena).unwrap();
+        assert_eq!(replacement.instantiate_in(&pattern.matches(&parse("x sin(z)")).unwrap(), &arena).to_string(), "2*sin(z)");
+
+        /* errors point into the input as written */
+        let error = Pattern::parse_in("?a + ?b + )", &arena).err().unwrap();
+        assert_eq!((error.kind, error.span), (ParseErrorKind::UnexpectedChar(')'), 10..11));
+
+        /* pattern rules on top of the standard ones */
+        let mut rules = Rules::default();
+        rules.register(Rewrite::from_patterns("pythagoras", "sin(?a)^2 + cos(?a)^2", "1").unwrap());
+        rules.register(Rewrite::from_patterns("ln product", "ln(?a) + ln(?b)", "ln(?a?b)").unwrap());
+
+        let simplified = parse("3*(cos(x)^2 + sin(x)^2) + (ln(x) + ln(y))").simplify_with(&rules, &mut (), &arena).unwrap();
+        assert_eq!(simplified.to_string(), "3 + ln(xy)");
+
+        /* longer sums combine a pair at a time */
+        let simplified = parse("ln(x) + ln(y) + ln(z)").simplify_with(&rules, &mut (), &arena).unwrap();
+        assert_eq!(simplified.to_string(), "ln(xyz)");
+
+        assert!(Rewrite::from_patterns("broken", "sin(?a", "1").is_err());
+
+        /* rules outlive the arenas they rewrite in */
+        for _ in 0..2 {
+            let arena = Bump::new();
+            let simplified = Term::parse_in("ln(a) + ln(b)", &arena).unwrap().simplify_with(&rules, &mut (), &arena).unwrap();
+            assert_eq!(simplified.to_string(), "ln(ab)");
+        }
+    }
+
+    #[test]
+    fn trigonometric_identities() {
+        use crate::Rules;
+
+        let arena = Bump::new();
+        let mut rules = Rules::default();
+        rules.register_trigonometry();
+
+        let simplify = |input| Term::parse_in(input, &arena).unwrap().simplify_with(&rules, &mut (), &arena).unwrap().to_string();
+
+        assert_eq!(simplify("3*sin(2t)^2 + 3*cos(2t)^2"), "3");
+        assert_eq!(simplify("sin(-2*x) + cos(-1*x)"), "-1*sin(2*x) + cos(x)");
+        assert_eq!(simplify("6*sin(x)cos(x)"), "3*sin(2*x)");
+        assert_eq!(simplify("cos(t)^2 + -1*sin(t)^2"), "cos(2*t)");
+        assert_eq!(simplify("sin(3*t) + sin(t)"), "2*sin(2*t)cos(t)");
+        assert_eq!(simplify("3*cos(2*t) + 4*sin(2*t)"), "5*sin(2*t + 0.6435011087932844)");
+
+        /* the standard rules leave all of these alone */
+        let standard = Term::parse_in("6*sin(x)cos(x)", &arena).unwrap().simplify_in(&arena).unwrap();
+        assert_eq!(standard.to_string(), "6*sin(x)cos(x)");
+
+        /* sin/cos pairs out of the inverse transform collapse into one sine */
+        let inverse = |input| *Term::parse_in(input, &arena).unwrap().inverse_laplace_in(Term::var('s').unwrap(), Term::var('t').unwrap(), &arena).unwrap();
+
+        assert_eq!(inverse("(3s + 4)(s^2 + 4)^-1").simplify_with(&rules, &mut (), &arena).unwrap().to_string(), "3.605551275463989*sin(2*t + 0.982793723247329)");
+        assert_eq!(
+            inverse("(s + 3)(s^2 + 2s + 5)^-1").simplify_with(&rules, &mut (), &arena).unwrap().to_string(),
+            "1.4142135623730951*e^(-1*t)sin(2*t + 0.7853981633974483)",
+        );
+    }
+
+    #[test]
+    fn symbolic_constants() {
+        use crate::{Rules, EvalError, Context, ParseErrorKind};
+
+        let arena = Bump::new();
+        let parse = |input| *Term::parse_in(input, &arena).unwrap();
+
+        for input in ["{k}x^2 + {m}y''", "ω₀^2y + 2ζω₀y'", "2πt", "e^(-1*t) + e", "{spring constant}"] {
+            let term = parse(input);
+            assert!(*Term::parse_in(&term.to_string(), &arena).unwrap() == term, "{term}");
+        }
+
+        assert!(matches!(parse("ω₀"), Symbol("ω₀")));
+        assert!(matches!(parse("ωt"), Product(..)));
+
+        /* parameters and both atoms stay exact */
+        assert_eq!(parse("2π + {k} + {k} + e").simplify_in(&arena).unwrap().canonicalize_in(&arena).unwrap().to_string(), "2*π + e + 2*{k}");
+        assert_eq!(parse("ln(e) + ln(e^x)").simplify_in(&arena).unwrap().to_string(), "1 + x");
+        let simplify = |input| parse(input).simplify_in(&arena).unwrap().to_string();
+        assert_eq!(simplify("e^2"), "e^2");
+        assert_eq!(simplify("e^(1/2)"), "e^0.5");
+        assert_eq!(simplify("π*2"), "2*π");
+        assert_eq!(simplify("e^1 + e^0"), "e + 1");
+        assert_eq!(parse("e^2").eval(&|_| None), Ok(std::f64::consts::E.powi(2)));
+        assert_eq!(parse("{k}x^2 + πx").differentiate_in(Term::var('x').unwrap(), &arena).unwrap().canonicalize_in(&arena).unwrap().to_string(), "π + 2*{k}x");
+
+        /* the transform treats them like numbers */
+        let laplace = |input| parse(input).laplace_in(Term::var('t').unwrap(), Term::var('s').unwrap(), &arena).unwrap().to_string();
+        assert_eq!(laplace("{k}"), "{k}/s");
+        assert_eq!(laplace("ω₀sin(2t)"), "ω₀(2/(s^2 + 4))");
+
+        /* they only turn into numbers when evaluated */
+        let env = |_: Name| Some(1.0);
+        assert_eq!(parse("2π").eval(&env), Ok(2.0 * std::f64::consts::PI));
+        assert_eq!(parse("{k}x").eval(&env), Err(EvalError::UnboundSymbol("k".to_string())));
+        assert_eq!(parse("{k}x").substitute_symbol_in("k", &Constant(Number::from(3)), &arena).eval(&env), Ok(3.0));
+
+        let ctx = Context::new(&arena);
+        let x = ctx.var('x').unwrap();
+        assert_eq!((ctx.symbol("k") * x + ctx.pi() + ctx.e().pow(x)).to_string(), "{k}x + π + e^x");
+
+        /* exact values at multiples of π/6 and π/4 */
+        let mut rules = Rules::default();
+        rules.register_trigonometry();
+
+        let simplify = |input| parse(input).simplify_with(&rules, &mut (), &arena).unwrap().to_string();
+        assert_eq!(simplify("sin(π)"), "0");
+        assert_eq!(simplify("cos(π)"), "-1");
+        assert_eq!(simplify("sin(π/6) + cos(2π/3)"), "0");
+        assert_eq!(simplify("sin(π/4)"), "0.5*2^0.5");
+        assert_eq!(simplify("cos(-5π/6)"), "-0.5*3^0.5");
+        assert_eq!(simplify("sin(π/5)"), "sin(0.2*π)");
+
+        /* the surds stay exact through canonical form */
+        let squared = parse("sin(π/4)^2 + cos(π/3)").simplify_with(&rules, &mut (), &arena).unwrap().canonicalize_in(&arena).unwrap();
+        assert!(*squared == Constant(Number::from(1)), "{squared}");
+        assert_eq!(parse("4^(1/2) + 8^(2/3)").simplify_in(&arena).unwrap().to_string(), "6");
+
+        assert_eq!(Term::parse_in("{k", &arena).err().map(|e| e.kind), Some(ParseErrorKind::Unclosed));
+    }
+
+    #[test]
+    fn exact_rationals() {
+        use crate::Context;
+
+        let arena = Bump::new();
+        let parse = |input| *Term::parse_in(input, &arena).unwrap();
+        let third = Number::ratio(1, 3).unwrap();
+
+        /* arithmetic on exact numbers stays exact */
+        assert_eq!(third + third + third, Number::from(1));
+        assert!((third + third + third).is_exact());
+        assert_eq!(Number::ratio(2, -4), Number::ratio(-1, 2));
+        assert_eq!(Number::ratio(1, 0), None);
+        let decimal = |input: &str| input.parse::<Number>().unwrap();
+        assert_eq!(decimal("0.1") + decimal("0.2"), decimal("0.3"));
+
+        /* floats spread, and so does overflow */
+        assert!(!(third + Number::from(0.5)).is_exact());
+        assert!(!(Number::from(i64::MAX) * Number::from(i64::MAX) * Number::from(i64::MAX)).is_exact());
+        assert!(!Number::from(2).pow(third).is_exact());
+        assert_eq!(Number::ratio(4, 9).unwrap().sqrt(), Number::ratio(2, 3).unwrap());
+
+        /* terminating fractions show as decimals, others as n/d, and both read back the same */
+        assert_eq!(Number::ratio(3, 4).unwrap().to_string(), "0.75");
+        assert_eq!(Number::ratio(-1, 8).unwrap().to_string(), "-0.125");
+        assert_eq!(third.to_string(), "1/3");
+        assert!(third.is_fraction() && !Number::ratio(3, 4).unwrap().is_fraction() && !Number::from(2).is_fraction());
+
+        let sum = parse("1/3 + 1/3 + 1/3").simplify_in(&arena).unwrap();
+        assert_eq!(sum.to_string(), "1");
+
+        let root = parse("x^(1/3)");
+        assert_eq!(root.to_string(), "x^(1/3)");
+        assert!(*Term::parse_in(&root.to_string(), &arena).unwrap() == root);
+        assert_eq!(root.differentiate_in(Term::var('x').unwrap(), &arena).unwrap().simplify_in(&arena).unwrap().to_string(), "1/3*x^(-2/3)");
+
+        /* integer literals from the builder are exact, f64 ones aren't */
+        let ctx = Context::new(&arena);
+        let x = ctx.var('x').unwrap();
+        assert!(matches!((x * 3).as_term(), Scale { coefficient: Number::Rational(3, 1), .. }));
+        assert!(matches!((x * 0.5).as_term(), Scale { coefficient: Number::Float(_), .. }));
+
+        /* partial fractions come out as exact coefficients */
+        let inverse = parse("(s^2 + 3s)^-1").inverse_laplace_in(Term::var('s').unwrap(), Term::var('t').unwrap(), &arena).unwrap();
+        assert_eq!(inverse.to_string(), "1/3 + -1/3*e^(-3*t)");
+    }
+
+    #[test]
+    fn named_variables() {
+        use crate::{Context, Equation, Pattern, EvalError, ParseError, ParseErrorKind};
+        use std::collections::HashMap;
+
+        let arena = Bump::new();
+        let parse = |input| *Term::parse_in(input, &arena).unwrap();
+        let named = |name| Term::Var(Name::new(name).unwrap());
+
+        /* spelled out greek letters and indexed names are single variables */
+        assert!(parse("theta") == named("theta"));
+        assert!(parse("i_L") == named("i_L"));
+        assert!(matches!(parse("x_1 x_2"), Product(..)));
+        assert!(matches!(parse("xy"), Product(..)));
+        assert!(parse("dtheta/dt") == parse("theta'"));
+        assert!(parse("d^2x_1/dt^2") == parse("x_1''"));
+
+        /* factors only get a `*` between them where juxtaposition would read as one name */
+        for (input, displayed) in [("x_1 x_2", "x_1*x_2"), ("t a u", "ta*u"), ("x i", "x*i"), ("v_C^2 i_L", "v_C^2i_L"), ("2theta", "2theta")] {
+            let term = parse(input);
+            assert_eq!(term.to_string(), displayed);
+            assert!(parse(displayed) == term, "{displayed}");
+        }
+
+        /* names have to follow the same rules everywhere */
+        let error = |kind, span| Err::<Name, _>(ParseError { kind, span });
+        assert_eq!(Name::new("omega_n").map(Name::as_str), Ok("omega_n"));
+        assert_eq!(Name::new("x y"), error(ParseErrorKind::UnexpectedChar(' '), 1..2));
+        assert_eq!(Name::new("x_"), error(ParseErrorKind::UnexpectedChar('_'), 1..2));
+        assert_eq!(Name::new(""), error(ParseErrorKind::UnexpectedEnd, 0..0));
+        assert!(Name::new("t").unwrap() == Name::letter('t').unwrap());
+        assert_eq!(Name::letter('1'), error(ParseErrorKind::UnexpectedChar('1'), 0..1));
+        assert_eq!(Name::letter('ω'), error(ParseErrorKind::UnexpectedChar('ω'), 0..2));
+        assert_eq!(Name::letter('e'), error(ParseErrorKind::ReservedName("e".to_string()), 0..1));
+        assert_eq!(Name::new("i"), error(ParseErrorKind::ReservedName("i".to_string()), 0..1));
+        assert_eq!(Name::new("i_L").map(Name::as_str), Ok("i_L"));
+
+        /* names are interned, so the same spelling shares one copy whichever arena parsed it */
+        let other = Bump::new();
+        let (Term::Var(ours), Term::Var(theirs)) = (*Term::parse_in("theta_1", &arena).unwrap(), *Term::parse_in("theta_1", &other).unwrap()) else {
+            panic!("names should parse as variables");
+        };
+        assert!(std::ptr::eq(ours.as_str(), theirs.as_str()));
+        assert!(ours == Name::new("theta_1").unwrap() && ours != Name::new("theta_2").unwrap());
+        assert!(Name::new("omega").unwrap() < Name::new("theta").unwrap());
+
+        let ctx = Context::new(&arena);
+        let (theta, omega) = (ctx.named("theta").unwrap(), ctx.named("omega").unwrap());
+        assert_eq!((ctx.sin(omega * theta) * ctx.var('x').unwrap()).to_string(), "sin(omegatheta)x");
+        assert!(ctx.named("2x").is_err());
+        assert!(ctx.var('_').is_err());
+
+        /* they differentiate, evaluate, match and solve like single letters */
+        let derivative = parse("v_C^2 + i_L v_C").differentiate_in(named("v_C"), &arena).unwrap();
+        assert_eq!(derivative.to_string(), "2*v_C + i_L");
+
+        let env = |v: Name| match v.as_str() { "theta" => Some(2.0), "x_1" => Some(3.0), _ => None };
+        assert_eq!(parse("theta x_1").eval(&env), Ok(6.0));
+        assert_eq!(parse("theta_0").eval(&env), Err(EvalError::Unbound("theta_0".to_string())));
+
+        let pattern = Pattern::parse_in("sin(?theta)^2", &arena).unwrap();
+        assert_eq!(pattern.wildcards, ["theta"]);
+        assert_eq!(pattern.matches(&parse("sin(omega t)^2")).unwrap()["theta"].to_string(), "omegat");
+
+        let circuit = Equation::new(vec![parse("i_L' + 2i_L")], vec![]);
+        let current = circuit.solve_ivp(named("i_L"), Term::var('t').unwrap(), &HashMap::from([(0, Number::from(1))]), &arena).unwrap();
+        assert_eq!(current.to_string(), "e^(-2*t)");
+    }
+
+    #[test]
+    fn switched_inputs() {
+        use crate::{Context, Equation, EvalError};
+        use std::collections::HashMap;
+
+        let arena = Bump::new();
+        let parse = |input| *Term::parse_in(input, &arena).unwrap();
+        let (t, s) = (|| Term::var('t').unwrap(), || Term::var('s').unwrap());
+
+        let ctx = Context::new(&arena);
+        let switched = ctx.piecewise(ctx.var('t').unwrap() - 1, 0, ctx.heaviside(ctx.var('t').unwrap())) + ctx.dirac(ctx.var('x').unwrap());
+        assert_eq!(switched.to_string(), "piecewise(t - 1, 0, heaviside(t)) + dirac(x)");
+        assert!(*Term::parse_in(&switched.to_string(), &arena).unwrap() == *switched.as_term());
+
+        /* second shifting theorem, L{u(t - a) f(t - a)} = e^(-as) F(s) */
+        let laplace = |input| parse(input).laplace_in(t(), s(), &arena).unwrap().to_string();
+        assert_eq!(laplace("heaviside(t - 2)"), "e^(-2*s)(1/s)");
+        assert_eq!(laplace("heaviside(t + 1)"), "1/s");
+        assert_eq!(laplace("heaviside(t - 1)t"), "e^(-1*s)(1/s^2 + 1/s)");
+        assert_eq!(laplace("heaviside(2 - t)"), "1/s + -1*e^(-2*s)(1/s)");
+        assert_eq!(laplace("dirac(t)"), "1");
+        assert_eq!(laplace("dirac(t - 3)"), "e^(-3*s)");
+        assert_eq!(laplace("piecewise(t - 2, 1, 0)"), "1/s + e^(-2*s)(-1/s)");
+
+        let inverse = |input| parse(input).inverse_laplace_in(s(), t(), &arena).unwrap().to_string();
+        assert_eq!(inverse("e^(-2s)/s"), "heaviside(t + -2)");
+        assert_eq!(inverse("(1 + e^(-s))/(s^2 + 1)"), "sin(t) + heaviside(t + -1)sin(t + -1)");
+        assert_eq!(inverse("s/(s + 1)"), "dirac(t) + -1*e^(-1*t)");
+        assert_eq!(inverse("e^(-s)"), "dirac(t + -1)");
+
+        /* steps jump where their argument crosses 0 */
+        let differentiate = |input| parse(input).differentiate_in(t(), &arena).unwrap().to_string();
+        assert_eq!(differentiate("heaviside(t - 2)"), "dirac(t + -2)");
+        assert_eq!(differentiate("piecewise(t - 1, t^2, 3t)"), "piecewise(t + -1, 2*t, 3) + (3*t + -1*t^2)dirac(t + -1)");
+        assert_eq!(differentiate("dirac(t - 1)"), "(dirac(t + -1), wrt: t)'");
+
+        let simplify = |input| parse(input).simplify_in(&arena).unwrap().to_string();
+        assert_eq!(simplify("heaviside(-2) + heaviside(0) + dirac(3)"), "1");
+        assert_eq!(simplify("heaviside(2*x) + dirac(-2*x)"), "heaviside(x) + 0.5*dirac(x)");
+        assert_eq!(simplify("piecewise(1, x, y) + piecewise(x, y, y)"), "2*y");
+
+        let env = |v: Name| (v == Name::letter('t').unwrap()).then_some(2.0);
+        assert_eq!(parse("piecewise(t - 1, 5, heaviside(t - 3) + 3)").eval(&env), Ok(3.0));
+        assert_eq!(parse("dirac(t - 2)").eval(&env), Err(EvalError::Domain("dirac(0)".to_string())));
+
+        /* switched circuits and impulse loads */
+        let solve = |lhs, rhs, initial: &[i32]| {
+            let equation = Equation::new(vec![parse(lhs)], vec![parse(rhs)]);
+            let initial = initial.iter().map(|&value| Number::from(value)).enumerate().collect();
+            equation.solve_ivp(Term::var('y').unwrap(), t(), &initial, &arena).unwrap().to_string()
+        };
+
+        assert_eq!(solve("y' + y", "heaviside(t - 1)", &[0]), "heaviside(t + -1) + heaviside(t + -1)(-1*e^(-1*t + 1))");
+        assert_eq!(solve("y'' + y", "dirac(t - 1)", &[0, 0]), "heaviside(t + -1)sin(t + -1)");
+        assert_eq!(solve("y'' + 4y", "5dirac(t)", &[1, 0]), "cos(2*t) + 2.5*sin(2*t)");
+    }
+
+    #[test]
+    fn quotients_and_negations() {
+        use crate::{Equation, EvalError, LadesError, Name};
+        use std::collections::HashMap;
+
+        let arena = Bump::new();
+        let parse = |input| Term::parse_in(input, &arena).unwrap();
+        let (x, s, t) = (Term::var('x').unwrap(), Term::var('s').unwrap(), Term::var('t').unwrap());
+
+        /* `-`, `/` and `1/` stay as written, and canonical form lowers them to scales and powers */
+        assert!(matches!(*parse("a - b/c"), Sum(_, ref b) if matches!(**b, Negation(ref q) if matches!(**q, Quotient(..)))));
+        assert!(matches!(*parse("1/x"), Reciprocal(_)));
+        let canonical = |input| parse(input).canonicalize_in(&arena).unwrap();
+        assert!(canonical("a/b - 1/c") == canonical("-1*c^-1 + ab^-1"));
+
+        let simplify = |input| parse(input).simplify_in(&arena).map(|term| term.to_string());
+        assert_eq!(simplify("x/2 - -(3*y) + 1/(1/z)"), Ok("0.5*x + 3*y + z".to_string()));
+        assert_eq!(simplify("2*(1/s) + 6/4"), Ok("2/s + 1.5".to_string()));
+        assert!(matches!(simplify("x/(2 - 2)"), Err(LadesError::DivisionByZero(_))));
+
+        /* the quotient rule */
+        let differentiate = |input| parse(input).differentiate_in(x.clone(), &arena).unwrap().to_string();
+        assert_eq!(differentiate("x/(x + 1)"), "1/(x + 1)^2");
+        assert_eq!(differentiate("1/sin(x)"), "-(cos(x)/sin(x)^2)");
+        assert_eq!(differentiate("-x^2"), "-2*x");
+
+        let env = |v: Name| (v == Name::letter('x').unwrap()).then_some(1.0);
+        assert_eq!(parse("-2/(x + 1) + 1/x").eval(&env), Ok(0.0));
+        assert_eq!(parse("1/(x - 1)").eval(&env), Err(EvalError::DivisionByZero("1/0".to_string())));
+
+        /* rational functions of `s` go both ways */
+        assert_eq!(parse("t - 1/2").laplace_in(t.clone(), s.clone(), &arena).unwrap().to_string(), "1/s^2 + -0.5/s");
+        assert_eq!(parse("(s + 3)/((s + 1)(s + 2)) - 1/s").inverse_laplace_in(s, t.clone(), &arena).unwrap().to_string(), "-1 + 2*e^(-1*t) + -1*e^(-2*t)");
+
+        /* subtracted and divided unknowns stay linear */
+        let solve = |lhs| Equation::new(vec![*parse(lhs)], vec![])
+            .solve_ivp(Term::var('y').unwrap(), t.clone(), &HashMap::from([(0, Number::from(1)), (1, Number::from(0))]), &arena)
+            .map(|y| y.to_string());
+        assert_eq!(solve("y'' - y"), Ok("0.5*e^t + 0.5*e^(-1*t)".to_string()));
+        assert_eq!(solve("y'' - y/4"), Ok("0.5*e^(0.5*t) + 0.5*e^(-0.5*t)".to_string()));
+        assert!(matches!(solve("y'' - 1/y"), Err(LadesError::NonLinear(_))));
+
+        let mut rules = crate::Rules::default();
+        rules.register_trigonometry();
+        let trigonometry = |input| parse(input).simplify_with(&rules, &mut (), &arena).unwrap().to_string();
+        assert_eq!(trigonometry("sin(-x)"), "-1*sin(x)");
+        assert_eq!(trigonometry("cos(-x)"), "cos(x)");
+        assert_eq!(trigonometry("cos(-(2*x))"), "cos(2*x)");
+    }
+
+    #[test]
+    fn polynomials() {
+        use crate::{LadesError, Polynomial};
+
+        let arena = Bump::new();
+        let parse = |input| Term::parse_in(input, &arena).unwrap();
+        let polynomial = |input| parse(input).polynomial_in(Term::var('s').unwrap(), &arena).unwrap();
+        let show = |p: &Polynomial| p.to_term_in(&arena).to_string();
+
+        let p = polynomial("(s + 1)(s - 2)^2 + {k}s/2");
+        assert_eq!(p.degree(), Some(3));
+        assert_eq!(show(&p), "s^3 + -3*s^2 + 0.5*{k}s + 4");
+        assert_eq!(p.terms().map(|(k, _)| k).collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+
+        let (p, q) = (polynomial("s^3 - 2s^2 - s + 2"), polynomial("s^2 + s - 2"));
+        assert_eq!(show(&p.add_in(&q, &arena).unwrap()), "s^3 + -1*s^2");
+        assert_eq!(show(&p.mul_in(&q, &arena).unwrap()), "s^5 + -1*s^4 + -5*s^3 + 5*s^2 + 4*s + -4");
+        assert_eq!(show(&p.derivative_in(&arena).unwrap()), "3*s^2 + -4*s + -1");
+        assert_eq!(show(&p.gcd_in(&q, &arena).unwrap()), "s + -1");
+
+        let (quotient, remainder) = p.div_rem_in(&polynomial("2s + 1"), &arena).unwrap();
+        assert_eq!((show(&quotient), show(&remainder)), ("0.5*s^2 + -1.25*s + 0.125".to_string(), "1.875".to_string()));
+        assert!(p.sub_in(&p, &arena).unwrap().is_zero());
+        assert_eq!(p.eval_in(&parse("3"), &arena).unwrap().to_string(), "8");
+        assert_eq!(p.eval_in(&parse("a + 1"), &arena).unwrap().to_string(), "(a + 1)((a + -1)(a + 1) + -1) + 2");
+
+        assert!(matches!(parse("1/s + s").polynomial_in(Term::var('s').unwrap(), &arena), Err(LadesError::NonPolynomial(_))));
+        assert!(matches!(p.div_rem_in(&polynomial("0"), &arena), Err(LadesError::DivisionByZero(_))));
+    }
+
+    #[test]
+    fn polynomial_roots() {
+        use crate::{LadesError, Root};
+
+        let arena = Bump::new();
+        let roots = |input| {
+            let polynomial = Term::parse_in(input, &arena).unwrap().polynomial_in(Term::var('s').unwrap(), &arena).unwrap();
+            polynomial.roots_in(&arena).map(|roots| roots.iter()
+                .map(|Root { re, im, multiplicity }| format!("{re}, {im} ({multiplicity})"))
+                .collect::<Vec<_>>()
+                .join("; "))
+        };
+
+        /* exact: rational roots, then whatever quadratic is left */
+        assert_eq!(roots("3s - 2"), Ok("2/3, 0 (1)".to_string()));
+        assert_eq!(roots("s^3(s - 1)^2(2s + 3)"), Ok("0, 0 (3); 1, 0 (2); -1.5, 0 (1)".to_string()));
+        assert_eq!(roots("s^2 + 2s + 5"), Ok("-1, 2 (1); -1, -2 (1)".to_string()));
+        assert_eq!(roots("(s - 1)(s^2 - 2s - 1)"), Ok("1, 0 (1); 1 + 2^0.5, 0 (1); 1 - 2^0.5, 0 (1)".to_string()));
+        assert_eq!(roots("s^2 + s + 1"), Ok("-0.5, 0.5*3^0.5 (1); -0.5, -(0.5*3^0.5) (1)".to_string()));
+
+        /* cubics with one real root and quartics in s^2 alone in radicals */
+        assert_eq!(roots("s^3 - 2"), Ok("2^(1/3), 0 (1); -0.5*2^(1/3), 0.5*3^0.5(2^(1/3)) (1); -0.5*2^(1/3), -(0.5*3^0.5(2^(1/3))) (1)".to_string()));
+        assert_eq!(roots("s^4 - 2"), Ok("2^0.25, 0 (1); -2^0.25, 0 (1); 0, 2^0.25 (1); 0, -2^0.25 (1)".to_string()));
+        assert_eq!(roots("s^4 - 4s^2 + 1"), Ok("(2 + 3^0.5)^0.5, 0 (1); -(2 + 3^0.5)^0.5, 0 (1); (2 - 3^0.5)^0.5, 0 (1); -(2 - 3^0.5)^0.5, 0 (1)".to_string()));
+        assert_eq!(roots("(s^2 + 0.5)^2"), Ok("0, 0.5*2^0.5 (2); 0, -(0.5*2^0.5) (2)".to_string()));
+
+        let evaluated = |input| {
+            let polynomial = Term::parse_in(input, &arena).unwrap().polynomial_in(Term::var('s').unwrap(), &arena).unwrap();
+            polynomial.roots_in(&arena).unwrap().iter().map(|root| (root.re.eval(&|_| None).unwrap(), root.im.eval(&|_| None).unwrap())).collect::<Vec<_>>()
+        };
+
+        let close = |roots: Vec<(f64, f64)>, expected: &[(f64, f64)]| roots.len() == expected.len()
+            && roots.iter().zip(expected).all(|((re, im), (a, b))| (re - a).abs() < 1e-12 && (im - b).abs() < 1e-12);
+
+        assert!(close(evaluated("s^3 - s - 1"), &[(1.324717957244746, 0.0), (-0.662358978622373, 0.5622795120623012), (-0.662358978622373, -0.5622795120623012)]));
+        let r = std::f64::consts::FRAC_1_SQRT_2;
+        assert!(close(evaluated("s^4 + 1"), &[(r, r), (r, -r), (-r, r), (-r, -r)]));
+
+        /* three irrational real roots, other quartics and aberth past degree 4 in floating point */
+        assert_eq!(roots("s^3 - 3s + 1"), Ok("1.532088886237956, 0 (1); 0.3472963553338607, 0 (1); -1.8793852415718169, 0 (1)".to_string()));
+        assert!(!roots("s^4 + s + 1").unwrap().contains('^'));
+        assert_eq!(roots("s^5 - s - 1").unwrap().matches("(1)").count(), 5);
+
+        assert!(matches!(roots("s + {k}"), Err(LadesError::Unsupported(_))));
+        assert!(matches!(roots("0"), Err(LadesError::Unsupported(_))));
+        assert!(crate::roots::numeric_roots(&[]).is_empty());
+        assert!(crate::roots::numeric_roots(&[2.0]).is_empty());
+    }
+
+    #[test]
+    fn complex_numbers() {
+        use crate::{Rules, EvalError, LadesError};
+
+        let arena = Bump::new();
+        let parse = |input| Term::parse_in(input, &arena).unwrap();
+        let simplify = |input| parse(input).simplify_in(&arena).unwrap().to_string();
+
+        assert_eq!(simplify("i^2"), "-1");
+        assert_eq!(simplify("i^7"), "-1*i");
+        assert_eq!(simplify("1/i"), "-1*i");
+        assert_eq!(parse("3i*2i + 1").canonicalize_in(&arena).unwrap().to_string(), "-5");
+        assert_eq!(parse("i^3 x").canonicalize_in(&arena).unwrap().to_string(), "-1*ix");
+
+        /* variables count as real */
+        let parts = |input| {
+            let term = parse(input);
+            (term.real_part_in(&arena).unwrap().to_string(), term.imaginary_part_in(&arena).unwrap().to_string())
+        };
+
+        assert_eq!(parts("(2 + 3i)(2 - 3i)"), ("13".to_string(), "0".to_string()));
+        assert_eq!(parts("(1 + i)/(1 - i)"), ("0".to_string(), "1".to_string()));
+        assert_eq!(parts("(x + i)^2").1, "2*x");
+        assert_eq!(parts("e^((-1 + 2i)t)").0, "e^(-1*t)cos(2*t)");
+        assert!(matches!(parse("sin(i)").real_part_in(&arena), Err(LadesError::Unsupported(_))));
+
+        /* powers multiply out by squaring, so large ones stay cheap */
+        assert_eq!(parts("(1 + i)^200"), ("1267650600228229401496703205376".to_string(), "0".to_string()));
+        assert_eq!(parts("(1 + i)^-2"), ("0".to_string(), "-0.5".to_string()));
+        assert_eq!(parts("(x + i)^3").1, "3*x^2 + -1");
+        assert!(matches!(parse("(-2)^(it)").real_part_in(&arena), Err(LadesError::Domain(_))));
+
+        /* euler's formula, both ways */
+        let (mut euler, mut exponential) = (Rules::default(), Rules::default());
+        euler.register_euler();
+        exponential.register_exponential_form();
+
+        let with = |rules: &Rules, input| parse(input).simplify_with(rules, &mut (), &arena).unwrap().to_string();
+
+        assert_eq!(with(&euler, "e^(i*t)"), "cos(t) + isin(t)");
+        assert_eq!(with(&euler, "e^((-1 + 2i)t)"), "e^(-1*t)cos(2*t) + i(e^(-1*t)sin(2*t))");
+        assert_eq!(with(&exponential, "cos(t) + i*sin(t)"), "e^(it)");
+        assert_eq!(with(&exponential, "2cos(3t) - 2i*sin(3t)"), "2*e^(-(i(3*t)))");
+
+        assert_eq!(parse("i").eval(&|_| None), Err(EvalError::Imaginary("i".to_string())));
+        assert_eq!(parse("isin(x)").to_string(), "isin(x)");
+    }
+
+    #[test]
+    fn undetermined_coefficients() {
+        use crate::{Equation, LadesError};
+        use std::collections::HashMap;
+
+        let arena = Bump::new();
+        let parse = |input| Term::parse_in(input, &arena).unwrap();
+        let equation = |lhs, rhs| Equation::new(vec![*parse(lhs)], vec![*parse(rhs)]);
+        let initial = |values: &[f64]| values.iter().map(|&value| Number::from(value)).enumerate().collect::<HashMap<_, _>>();
+        let solve = |lhs, rhs, values| equation(lhs, rhs).solve_ivp_undetermined(Term::var('y').unwrap(), Term::var('t').unwrap(), &initial(values), &arena);
+
+        /* resonance: the guesses for sin(t) and te^t get multiplied by t and t^2 */
+        let cases = [
+            ("y'' + 3y' + 2y", "0", &[1.0, 0.0][..], "2*e^(-1*t) + -1*e^(-2*t)"),
+            ("y' + 2y", "e^(-t)", &[0.0], "-1*e^(-2*t) + e^(-1*t)"),
+            ("y'' + y", "sin(t)", &[0.0, 1.0], "1.5*sin(t) + -0.5*tcos(t)"),
+            ("y'' - 2y' + y", "t e^t + 3", &[1.0, -1.0], "-2*e^t + te^t + 1/6*t^3e^t + 3"),
+            ("y''' - y'", "2t + 5", &[0.0, 1.0, 0.0], "4*e^t + -2 + -2*e^(-1*t) + -5*t + -1*t^2"),
+        ];
+
+        for (lhs, rhs, values, expected) in cases {
+            assert_eq!(solve(lhs, rhs, values).unwrap().to_string(), expected);
+        }
+
+        /* the laplace path agrees */
+        let no_vars = |_: Name| None;
+        let points = [0.0, 0.5, 1.0, 2.5];
+        let (lhs, rhs) = ("y'' + 4y", "t^2 - cos(2t) + e^(-t)sin(3t)");
+
+        let undetermined = solve(lhs, rhs, &[0.5, 0.0]).unwrap().eval_over(Name::letter('t').unwrap(), &points, &no_vars).unwrap();
+        let laplace = equation(lhs, rhs).solve_ivp(Term::var('y').unwrap(), Term::var('t').unwrap(), &initial(&[0.5, 0.0]), &arena).unwrap()
+            .eval_over(Name::letter('t').unwrap(), &points, &no_vars).unwrap();
+
+        assert!(undetermined.iter().zip(&laplace).all(|(a, b)| (a - b).abs() < 1e-9));
+
+        assert!(matches!(solve("y' + y", "ln(t)", &[0.0]), Err(LadesError::Unsupported(_))));
+        assert!(matches!(solve("y'' + y", "1", &[0.0]), Err(LadesError::MissingInitialCondition(y, 1)) if y == "y"));
+    }
+
+    #[test]
+    fn integrals() {
+        use crate::{EvalError, LadesError};
+
+        let arena = Bump::new();
+        let parse = |input| Term::parse_in(input, &arena).unwrap();
+        let integrate = |input| parse(input).integrate_in(Term::var('t').unwrap(), &arena).unwrap().to_string();
+
+        assert_eq!(integrate("t^3 + 2t + 3"), "3*t + t^2 + 0.25*t^4");
+        assert_eq!(integrate("1/t + (2t + 1)^-2"), "0.5*ln(t^2) + -0.5*(2*t + 1)^-1");
+        assert_eq!(parse("1/t").integrate_in(Term::var('t').unwrap(), &arena).unwrap().eval(&|_| Some(-2.0)), Ok(2f64.ln()));
+        assert_eq!(integrate("e^(2t) + 2^t"), "1.4426950408889634*2^t + 0.5*e^(2*t)");
+        assert_eq!(integrate("sin(3t) - cos(t/2)"), "-2*sin(0.5*t) + -1/3*cos(3*t)");
+        assert_eq!(integrate("{k}cos(t)^2"), "{k}(0.5*t + 0.25*sin(2*t))");
+
+        /* by parts, and the cyclic case */
+        assert_eq!(integrate("t e^(2t)"), "0.5*te^(2*t) + -0.25*e^(2*t)");
+        assert_eq!(integrate("t^2 sin(t)"), "2*tsin(t) + -1*t^2cos(t) + 2*cos(t)");
+        assert_eq!(integrate("e^t sin(2t)"), "e^t(0.2*sin(2*t) + -0.4*cos(2*t))");
+
+        /* what's left stays an integral, which differentiates back and round-trips */
+        let left = parse("3 + e^(2t)/t").integrate_in(Term::var('t').unwrap(), &arena).unwrap();
+        assert_eq!(left.to_string(), "3*t + integral(t^-1e^(2*t), wrt: t)");
+        assert!(*Term::parse_in(&left.to_string(), &arena).unwrap() == *left);
+        assert_eq!(left.clone().differentiate_in(Term::var('t').unwrap(), &arena).unwrap().to_string(), "3 + t^-1e^(2*t)");
+        assert!(matches!(left.eval(&|_| Some(1.0)), Err(EvalError::Integral(_))));
+
+        assert!(matches!(parse("t").integrate_in(Constant(Number::from(1)), &arena), Err(LadesError::NonVariable(_))));
+
+        /* exponentials need a positive base, and 1^t is just 1 */
+        assert_eq!(integrate("1^t + 1^(2t)sin(t)"), "t + -1*cos(t)");
+        assert!(matches!(parse("(-2)^t").integrate_in(Term::var('t').unwrap(), &arena), Err(LadesError::Domain(_))));
+        assert!(matches!(parse("0^t cos(t)").integrate_in(Term::var('t').unwrap(), &arena), Err(LadesError::Domain(_))));
+    }
+
+    #[test]
+    fn variation_of_parameters() {
+        use crate::{Equation, LadesError};
+
+        let arena = Bump::new();
+        let parse = |input| *Term::parse_in(input, &arena).unwrap();
+        let particular = |lhs, rhs| Equation::new(vec![parse(lhs)], vec![parse(rhs)])
+            .particular_solution_in(Term::var('y').unwrap(), Term::var('t').unwrap(), &arena)
+            .map(|solution| solution.to_string());
+
+        /* closed forms */
+        assert_eq!(particular("y''", "1/t^2"), Ok("-0.5*ln(t^2) + -1".to_string()));
+        assert_eq!(particular("y' + 2y", "t"), Ok("0.5*t + -0.25".to_string()));
+        assert_eq!(particular("y'' - y", "t^2"), Ok("-1*t^2 + -2".to_string()));
+        assert_eq!(particular("y''' - y'", "t"), Ok("-0.5*t^2 + -1".to_string()));
+        assert_eq!(particular("y'' + 3y' + 2y", "e^(-t)"), Ok("te^(-1*t) + -1*e^(-1*t)".to_string()));
+
+        /* no closed form for (part of) the integrals */
+        assert_eq!(particular("y'' + y", "sin(t)/cos(t)"), Ok("-1*sin(t)cos(t) + -1*cos(t)integral(sin(t)^2cos(t)^-1, wrt: t)".to_string()));
+        assert_eq!(particular("y'' + y", "1/t"), Ok("sin(t)integral(t^-1cos(t), wrt: t) + -1*cos(t)integral(t^-1sin(t), wrt: t)".to_string()));
+
+        assert_eq!(particular("y'' + y", "0"), Ok("0".to_string()));
+        assert!(matches!(particular("y y'", "t"), Err(LadesError::NonLinear(_))));
     }
 }
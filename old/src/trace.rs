@@ -0,0 +1,172 @@
+use crate::{Term, ArenaTerm, LadesError};
+use crate::rewrite::{Rules, SimplifyObserver};
+use crate::simplify::STANDARD_RULES;
+use bumpalo::Bump;
+use std::fmt::{self, Write};
+
+/// one rule application during a simplification.
+#[derive(Clone)]
+pub struct Step<'arena> {
+    pub rule: String,
+    /// the subterm the rule matched.
+    pub before: Term<'arena>,
+    pub after: Term<'arena>,
+    pub depth: usize,
+    /// which child to take at each level, from the root down to `before`.
+    pub path: Vec<usize>,
+}
+
+/// every step a simplification took, in order.
+#[derive(Clone, Default)]
+pub struct SimplifyTrace<'arena> {
+    pub input: Option<Term<'arena>>,
+    pub steps: Vec<Step<'arena>>,
+
+    /* where the pass currently is: the path to the last node entered, and how many children of each node
+     * on it were entered so far */
+    path: Vec<usize>,
+    children_entered: Vec<usize>,
+}
+
+impl<'arena> SimplifyObserver<'arena> for SimplifyTrace<'arena> {
+    fn enter(&mut self, term: &Term<'arena>, depth: usize) {
+        if depth == 0 {
+            self.input.get_or_insert_with(|| term.clone());
+            self.path.clear();
+            self.children_entered = vec![0];
+            return;
+        }
+
+        let index = self.children_entered[depth - 1];
+        self.children_entered[depth - 1] += 1;
+        self.children_entered.truncate(depth);
+        self.children_entered.push(0);
+
+        self.path.truncate(depth - 1);
+        self.path.push(index);
+    }
+
+    fn rewrite(&mut self, rule: &str, before: &Term<'arena>, after: &Term<'arena>, depth: usize) {
+        self.steps.push(Step {
+            rule: rule.to_string(),
+            before: before.clone(),
+            after: after.clone(),
+            depth,
+            path: self.path[..depth].to_vec(),
+        });
+    }
+}
+
+impl<'arena> SimplifyTrace<'arena> {
+    /// the whole term after each step, starting with the input.
+    pub fn expressions(&self) -> Vec<Term<'arena>> {
+        let Some(input) = &self.input else { return Vec::new() };
+        let mut current = input.clone();
+        let mut expressions = vec![current.clone()];
+
+        for step in &self.steps {
+            *current.subterm_mut(&step.path) = step.after.clone();
+            expressions.push(current.clone());
+        }
+
+        expressions
+    }
+
+    /// a "show your work" derivation: the input, then one `= ...` line per step naming the rule.
+    pub fn derivation(&self) -> String {
+        let expressions = self.expressions();
+        let Some((input, rest)) = expressions.split_first() else { return String::new() };
+
+        let mut derivation = format!("{input}\n");
+
+        for (expression, step) in rest.iter().zip(&self.steps) {
+            writeln!(derivation, "= {expression}    ({}: {} => {})", step.rule, step.before, step.after).unwrap();
+        }
+
+        derivation
+    }
+
+    pub fn to_json(&self) -> String {
+        let steps: Vec<String> = self.steps.iter().map(|step| format!(
+            r#"{{"rule": {}, "before": {}, "after": {}, "depth": {}, "path": [{}]}}"#,
+            json_string(&step.rule),
+            json_string(&step.before.to_string()),
+            json_string(&step.after.to_string()),
+            step.depth,
+            step.path.iter().map(usize::to_string).collect::<Vec<_>>().join(", "),
+        )).collect();
+
+        let input = match &self.input {
+            Some(input) => json_string(&input.to_string()),
+            None => "null".to_string(),
+        };
+
+        format!(r#"{{"input": {input}, "steps": [{}]}}"#, steps.join(", "))
+    }
+}
+
+/// one line per step, indented by depth.
+impl fmt::Display for SimplifyTrace<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for step in &self.steps {
+            writeln!(f, "{}{} => {}    [{}]", "   ".repeat(step.depth), step.before, step.after, step.rule)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'arena> Term<'arena> {
+    /// `simplify_in`, also returning the steps it took.
+    pub fn simplify_traced_in(self, arena: &'arena Bump) -> Result<(ArenaTerm<'arena>, SimplifyTrace<'arena>), LadesError> {
+        self.simplify_traced_with(&STANDARD_RULES, arena)
+    }
+
+    pub fn simplify_traced_with(self, rules: &Rules, arena: &'arena Bump) -> Result<(ArenaTerm<'arena>, SimplifyTrace<'arena>), LadesError> {
+        let mut trace = SimplifyTrace::default();
+        let simplified = self.simplify_with(rules, &mut trace, arena)?;
+
+        Ok((simplified, trace))
+    }
+
+    /// the subterm at `path`, numbering children the way `try_map_children` visits them.
+    fn subterm_mut(&mut self, path: &[usize]) -> &mut Term<'arena> {
+        use Term::*;
+
+        let Some((&index, rest)) = path.split_first() else { return self };
+
+        let child = match (self, index) {
+            (Sum(t1, _) | Product(t1, _) | Quotient(t1, _), 0) => t1,
+            (Sum(_, t2) | Product(_, t2) | Quotient(_, t2), 1) => t2,
+            (Power { base, .. }, 0) => base,
+            (Power { exponent, .. }, 1) => exponent,
+            (Derivative { wrt, .. } | Integral { wrt, .. }, 0) => wrt,
+            (Derivative { term, .. } | Integral { term, .. }, 1) => term,
+            (Piecewise { switch, .. }, 0) => switch,
+            (Piecewise { below, .. }, 1) => below,
+            (Piecewise { above, .. }, 2) => above,
+            (Scale { term, .. } | Exponential(_, term) | Sin(term) | Cos(term) | Ln(term), 0) => term,
+            (Negation(term) | Reciprocal(term) | Heaviside(term) | Dirac(term), 0) => term,
+            _ => unreachable!("paths come from the same traversal"),
+        };
+
+        child.subterm_mut(rest)
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut escaped = String::from('"');
+
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            c if c.is_control() => write!(escaped, "\\u{:04x}", c as u32).unwrap(),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped.push('"');
+    escaped
+}
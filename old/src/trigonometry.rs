@@ -0,0 +1,217 @@
+use crate::{Term, ArenaTerm, LadesError, Number, Rewrite, Rules};
+use bumpalo::Bump;
+
+impl Rules {
+    /// adds the (opt-in) trigonometric identities, turning e.g. `3cos(2t) + 4sin(2t)` into `5sin(2t + 0.6435011087932844)`.
+    pub fn register_trigonometry(&mut self) -> &mut Self {
+        use Term::*;
+
+        self
+            /* sin and cos at multiples of π/6 and π/4 */
+            .register(Rewrite::new("exact values", |term, arena| Ok(match term {
+                Sin(angle) => twelfths_of_pi(angle, arena)?.map(|n| sine_at(n, arena)),
+                Cos(angle) => twelfths_of_pi(angle, arena)?.map(|n| sine_at(n + 6, arena)),
+                _ => None,
+            })))
+
+            /* k sin(a)^2 + k cos(a)^2 = k */
+            .register(Rewrite::new("pythagoras", |term, _| Ok(match term {
+                Sum(t1, t2) => match (split(t1), split(t2)) {
+                    ((k1, f1, Power { base: b1, exponent: e1 }), (k2, f2, Power { base: b2, exponent: e2 }))
+                        if k1 == k2 && f1.is_none() && f2.is_none() && **e1 == Constant(Number::from(2)) && **e2 == Constant(Number::from(2)) =>
+                    {
+                        match (&**b1, &**b2) {
+                            (Sin(a1), Cos(a2)) | (Cos(a1), Sin(a2)) if a1 == a2 => Some(Constant(k1)),
+                            _ => None,
+                        }
+                    },
+                    _ => None,
+                },
+                _ => None,
+            })))
+
+            /* sin(-x) = -sin(x) */
+            .register(Rewrite::new("odd sine", |term, arena| Ok(match term {
+                Sin(inner) => match scaled(inner) {
+                    (k, term) if k.is_negative() => {
+                        let sine = Sin(ArenaTerm::new_in(times(-k, term.clone(), arena), arena));
+                        Some(times(Number::from(-1), sine, arena))
+                    },
+                    _ => None,
+                },
+                _ => None,
+            })))
+
+            /* cos(-x) = cos(x) */
+            .register(Rewrite::new("even cosine", |term, arena| Ok(match term {
+                Cos(inner) => match scaled(inner) {
+                    (k, term) if k.is_negative() => Some(Cos(ArenaTerm::new_in(times(-k, term.clone(), arena), arena))),
+                    _ => None,
+                },
+                _ => None,
+            })))
+
+            /* k sin(a)cos(a) = k/2 sin(2a) */
+            .register(Rewrite::new("double angle sine", |term, arena| Ok({
+                let (k, product) = scaled(term);
+
+                match product {
+                    Product(t1, t2) => match (&**t1, &**t2) {
+                        (Sin(a1), Cos(a2)) | (Cos(a2), Sin(a1)) if a1 == a2 => Some(times(k / Number::from(2), Sin(double(a1, arena)), arena)),
+                        _ => None,
+                    },
+                    _ => None,
+                }
+            })))
+
+            /* k cos(a)^2 - k sin(a)^2 = k cos(2a) */
+            .register(Rewrite::new("double angle cosine", |term, arena| Ok(match term {
+                Sum(t1, t2) => match (split(t1), split(t2)) {
+                    ((k1, None, Power { base: b1, exponent: e1 }), (k2, None, Power { base: b2, exponent: e2 }))
+                        if k1 == -k2 && **e1 == Constant(Number::from(2)) && **e2 == Constant(Number::from(2)) =>
+                    {
+                        match (&**b1, &**b2) {
+                            (Cos(a1), Sin(a2)) if a1 == a2 => Some(times(k1, Cos(double(a1, arena)), arena)),
+                            (Sin(a1), Cos(a2)) if a1 == a2 => Some(times(k2, Cos(double(a1, arena)), arena)),
+                            _ => None,
+                        }
+                    },
+                    _ => None,
+                },
+                _ => None,
+            })))
+
+            /* k sin(a) + k sin(b) = 2k sin((a + b)/2) cos((a - b)/2)
+             * k cos(a) + k cos(b) = 2k cos((a + b)/2) cos((a - b)/2) */
+            .register(Rewrite::new("sum to product", |term, arena| Ok(match term {
+                Sum(t1, t2) => match (split(t1), split(t2)) {
+                    ((k1, None, first), (k2, None, second)) if k1 == k2 => {
+                        let (sine, a, b) = match (first, second) {
+                            (Sin(a), Sin(b)) if a != b => (true, a, b),
+                            (Cos(a), Cos(b)) if a != b => (false, a, b),
+                            _ => return Ok(None),
+                        };
+
+                        let half = |difference: Number| Scale {
+                            coefficient: Number::from(1) / Number::from(2),
+                            term: ArenaTerm::new_in(Sum(a.clone(), ArenaTerm::new_in(Scale { coefficient: difference, term: b.clone() }, arena)), arena),
+                        }.canonicalize_in(arena);
+
+                        let (mean, spread) = (half(Number::from(1))?, half(Number::from(-1))?);
+
+                        Some(times(Number::from(2) * k1, Product(
+                            ArenaTerm::new_in(if sine { Sin(mean) } else { Cos(mean) }, arena),
+                            ArenaTerm::new_in(Cos(spread), arena),
+                        ), arena))
+                    },
+                    _ => None,
+                },
+                _ => None,
+            })))
+
+            /* A f sin(u) + B f cos(u) = R f sin(u + p), with R = sqrt(A^2 + B^2) and tan(p) = B/A */
+            .register(Rewrite::new("amplitude phase", |term, arena| Ok(match term {
+                Sum(t1, t2) => match (split(t1), split(t2)) {
+                    ((k1, f1, first), (k2, f2, second)) if f1 == f2 => {
+                        let (a, b, u) = match (first, second) {
+                            (Sin(u1), Cos(u2)) if u1 == u2 => (k1, k2, u1),
+                            (Cos(u1), Sin(u2)) if u1 == u2 => (k2, k1, u1),
+                            _ => return Ok(None),
+                        };
+
+                        let shifted = Sin(ArenaTerm::new_in(Sum(u.clone(), ArenaTerm::new_in(Constant(Number::from(b.to_f64().atan2(a.to_f64()))), arena)), arena));
+
+                        Some(times((a * a + b * b).sqrt(), match f1 {
+                            Some(f) => Product(ArenaTerm::new_in(f.clone(), arena), ArenaTerm::new_in(shifted, arena)),
+                            None => shifted,
+                        }, arena))
+                    },
+                    _ => None,
+                },
+                _ => None,
+            })))
+    }
+}
+
+/// `(k, t)` with the term being `k*t`.
+fn scaled<'t, 'a>(term: &'t Term<'a>) -> (Number, &'t Term<'a>) {
+    match term {
+        Term::Scale { coefficient, term } => {
+            let (k, term) = scaled(term);
+            (*coefficient * k, term)
+        },
+        Term::Negation(term) => {
+            let (k, term) = scaled(term);
+            (-k, term)
+        },
+        _ => (Number::from(1), term),
+    }
+}
+
+/// `(k, f, g)` with the term being `k*f*g`, where `g` is a sine, cosine or a power of either.
+pub(crate) fn split<'t, 'a>(term: &'t Term<'a>) -> (Number, Option<&'t Term<'a>>, &'t Term<'a>) {
+    use Term::*;
+
+    let is_trig = |term: &Term| match term {
+        Sin(_) | Cos(_) => true,
+        Power { base, .. } => matches!(**base, Sin(_) | Cos(_)),
+        _ => false,
+    };
+
+    let (k, term) = scaled(term);
+
+    match term {
+        Product(f, g) if is_trig(g) => (k, Some(f), g),
+        Product(g, f) if is_trig(g) => (k, Some(f), g),
+        _ => (k, None, term),
+    }
+}
+
+/// `n` if the angle is `nπ/12`, with `n` a multiple of 2 or 3, so a multiple of π/6 or π/4.
+fn twelfths_of_pi<'a>(angle: &Term<'a>, arena: &'a Bump) -> Result<Option<i128>, LadesError> {
+    let angle = angle.clone().canonicalize_in(arena)?;
+
+    Ok(match scaled(&angle) {
+        (c, Term::Pi) => (Number::from(12) * c).to_integer().filter(|n| n % 2 == 0 || n % 3 == 0),
+        _ => None,
+    })
+}
+
+/// `sin(nπ/12)`, exactly: a rational, or half of `2^(1/2)` or `3^(1/2)`.
+fn sine_at<'a>(n: i128, arena: &'a Bump) -> Term<'a> {
+    use Term::*;
+
+    let n = n.rem_euclid(24);
+
+    /* sin(x + π) = -sin(x), sin(π - x) = sin(x) */
+    let (sign, n) = if n >= 12 { (Number::from(-1), n - 12) } else { (Number::from(1), n) };
+    let half = sign / Number::from(2);
+
+    let surd = |k| Scale {
+        coefficient: half,
+        term: ArenaTerm::new_in(Power {
+            base: ArenaTerm::new_in(Constant(Number::from(k)), arena),
+            exponent: ArenaTerm::new_in(Constant(Number::from(1) / Number::from(2)), arena),
+        }, arena),
+    };
+
+    match n.min(12 - n) {
+        0 => Constant(Number::from(0)),
+        2 => Constant(half),
+        3 => surd(2),
+        4 => surd(3),
+        _ => Constant(sign),
+    }
+}
+
+/// `k*term`, without a `Scale` when `k` is 1.
+pub(crate) fn times<'a>(k: Number, term: Term<'a>, arena: &'a Bump) -> Term<'a> {
+    match k {
+        _ if k.is_one() => term,
+        _ => Term::Scale { coefficient: k, term: ArenaTerm::new_in(term, arena) },
+    }
+}
+
+fn double<'a>(angle: &ArenaTerm<'a>, arena: &'a Bump) -> ArenaTerm<'a> {
+    ArenaTerm::new_in(Term::Scale { coefficient: Number::from(2), term: angle.clone() }, arena)
+}
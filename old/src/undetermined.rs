@@ -0,0 +1,334 @@
+use crate::{Term, ArenaTerm, LadesError, Number, Name, Equation};
+use crate::complex::Complex;
+use crate::equation::as_var;
+use crate::inverse_laplace::{tidy, taylor, time_factor, time_scaled};
+use crate::roots::evaluated_roots;
+use bumpalo::Bump;
+use std::collections::HashMap;
+
+/// `Re(e^(rate t) sum(coefficients[k] t^k))`, the shape of the forcing terms the method handles and
+/// of the particular solutions it guesses for them.
+struct Family {
+    rate: Complex,
+    coefficients: Vec<Complex>,
+}
+
+impl<'a> Equation<'a> {
+    /// solves the same initial value problem as `solve_ivp`, but by the method of undetermined coefficients:
+    /// the homogeneous solution comes from the roots of the characteristic polynomial and the particular one
+    /// from a guess shaped like the forcing, which has to be a sum of products of powers of `t`, exponentials,
+    /// and (at most one) sine or cosine each.
+    pub fn solve_ivp_undetermined(
+        &self,
+        unknown: Term,
+        wrt_time: Term,
+        initial_conditions: &HashMap<usize, Number>,
+        arena: &'a Bump
+    ) -> Result<ArenaTerm<'a>, LadesError> {
+        use Term::*;
+
+        let y = as_var(unknown)?;
+        let t = as_var(wrt_time)?;
+        let ode = self.linear_in(&[y], t, arena)?;
+
+        let characteristic: Vec<f64> = ode.coefficients[0].iter().map(|a| a.to_f64()).collect();
+        let order = characteristic.iter().rposition(|&a| a != 0.0).ok_or_else(|| LadesError::NoUnknown(y.to_string()))?;
+        let characteristic = &characteristic[..=order];
+        let roots = evaluated_roots(&ode.coefficients[0][..=order], arena)?;
+
+        let mut forcing: Vec<Family> = Vec::new();
+
+        for term in &ode.forcing {
+            let term = term.clone().canonicalize_in(arena)?;
+
+            for (k, summand) in summands(&term) {
+                let family = Family::of(summand, t).ok_or_else(|| LadesError::Unsupported(summand.to_string()))?;
+                let family = Family { coefficients: family.coefficients.into_iter().map(|c| c * Complex::from(k)).collect(), ..family };
+
+                match forcing.iter_mut().find(|like| close(like.rate, family.rate)) {
+                    Some(like) => like.add(&family),
+                    None => forcing.push(family),
+                }
+            }
+        }
+
+        let particular: Vec<Family> = forcing.iter().map(|family| family.particular(characteristic, &roots)).collect();
+
+        let basis = fundamental_set(&roots);
+
+        /* the homogeneous coefficients make up whatever the particular solution misses of the initial conditions */
+        let mut matrix = Vec::with_capacity(order);
+        let mut rhs = Vec::with_capacity(order);
+
+        for k in 0..order {
+            let value = initial_conditions.get(&k).copied().ok_or_else(|| LadesError::MissingInitialCondition(y.to_string(), k))?;
+
+            matrix.push(basis.iter().map(|&(j, rate, imaginary)| match imaginary {
+                true => derivative_at_zero(k, j, rate).im,
+                false => derivative_at_zero(k, j, rate).re,
+            }).collect::<Vec<_>>());
+
+            rhs.push(value.to_f64() - particular.iter().map(|family| family.derivative_at_zero(k)).sum::<f64>());
+        }
+
+        let homogeneous = gaussian_elimination(matrix, rhs).ok_or(LadesError::Singular)?;
+
+        let mut terms = Vec::new();
+
+        for (&solution, c) in basis.iter().zip(homogeneous) {
+            push(&mut terms, c, solution_in(t, solution, arena), arena);
+        }
+
+        /* Re(h e^(ibt)) = Re(h) cos(bt) - Im(h) sin(bt) */
+        for family in &particular {
+            let (a, b) = (tidy(family.rate.re), tidy(family.rate.im));
+
+            for (j, h) in family.coefficients.iter().enumerate() {
+                match b.is_zero() {
+                    true => push(&mut terms, h.re, time_factor(t, j, a, None, arena), arena),
+                    false => {
+                        push(&mut terms, h.re, time_factor(t, j, a, Some(Cos(time_scaled(t, b, arena))), arena), arena);
+                        push(&mut terms, -h.im, time_factor(t, j, a, Some(Sin(time_scaled(t, b, arena))), arena), arena);
+                    },
+                }
+            }
+        }
+
+        terms.into_iter()
+            .map(|term| ArenaTerm::new_in(term, arena))
+            .reduce(|sum, term| ArenaTerm::new_in(Sum(sum, term), arena))
+            .map_or_else(|| Ok(ArenaTerm::new_in(Constant(Number::from(0)), arena)), |solution| solution.simplify_in(arena))
+    }
+}
+
+impl Family {
+    /// the family of a single product, e.g. `3t^2 e^(-t) sin(2t)`.
+    fn of(term: &Term, t: Name) -> Option<Self> {
+        let mut family = Family { rate: Complex::ZERO, coefficients: vec![Complex::ONE] };
+        let mut trig = false;
+
+        family.collect(term, t, &mut trig)?;
+        Some(family)
+    }
+
+    /// multiplies the family by one factor of a product.
+    fn collect(&mut self, term: &Term, t: Name, trig: &mut bool) -> Option<()> {
+        use Term::*;
+
+        let scale = |family: &mut Family, c: Complex| family.coefficients.iter_mut().for_each(|a| *a = *a * c);
+
+        match term {
+            _ if !term.contains_var(t) => scale(self, Complex::from(term.eval(&|_| None).ok()?)),
+
+            Var(_) => self.coefficients.insert(0, Complex::ZERO),
+
+            Scale { coefficient, term } => {
+                scale(self, Complex::from(coefficient.to_f64()));
+                self.collect(term, t, trig)?;
+            },
+
+            Negation(term) => {
+                scale(self, Complex::from(-1.0));
+                self.collect(term, t, trig)?;
+            },
+
+            Product(t1, t2) => {
+                self.collect(t1, t, trig)?;
+                self.collect(t2, t, trig)?;
+            },
+
+            Power { base, exponent } if **base == Var(t) => {
+                let Constant(n) = **exponent else {
+                    return None;
+                };
+
+                let n = usize::try_from(n.to_integer()?).ok()?;
+                self.coefficients.splice(0..0, std::iter::repeat_n(Complex::ZERO, n));
+            },
+
+            /* b^(kt + c) = b^c e^(k ln(b) t) */
+            Exponential(base, exponent) => {
+                let (slope, intercept) = exponent.linear_coefficients(t)?;
+                self.rate = self.rate + Complex::from((slope * base.ln()).to_f64());
+                scale(self, Complex::from(base.pow(intercept).to_f64()));
+            },
+
+            /* cos(wt + φ) = Re(e^(iφ) e^(iwt)), sin(wt + φ) = Re(-i e^(iφ) e^(iwt)), with w > 0 */
+            Sin(angle) | Cos(angle) if !*trig => {
+                let (w, phase) = angle.linear_coefficients(t)?;
+                let (w, phase) = (w.to_f64(), phase.to_f64());
+
+                let (w, phase, sign) = match w < 0.0 {
+                    true => (-w, -phase, if matches!(term, Sin(_)) { -1.0 } else { 1.0 }),
+                    false => (w, phase, 1.0),
+                };
+
+                let c = match term {
+                    Sin(_) => Complex::new(0.0, -sign),
+                    _ => Complex::ONE,
+                };
+
+                scale(self, c * Complex::polar(1.0, phase));
+                self.rate = self.rate + Complex::new(0.0, w);
+                *trig = true;
+            },
+
+            _ => return None,
+        }
+
+        Some(())
+    }
+
+    fn add(&mut self, other: &Family) {
+        if self.coefficients.len() < other.coefficients.len() {
+            self.coefficients.resize(other.coefficients.len(), Complex::ZERO);
+        }
+
+        for (sum, &c) in self.coefficients.iter_mut().zip(&other.coefficients) {
+            *sum = *sum + c;
+        }
+    }
+
+    /// the particular solution for this forcing: `e^(rate t) sum(h_k t^(k+s))`, with `s` the multiplicity
+    /// of `rate` as a characteristic root, so no part of the guess solves the homogeneous equation.
+    fn particular(&self, characteristic: &[f64], roots: &[(Complex, usize)]) -> Family {
+        let s = roots.iter().find(|(root, _)| close(*root, self.rate)).map_or(0, |&(_, m)| m);
+        let n = self.coefficients.len() - 1;
+
+        /* with p^(i)(r) = i! d[i], L[t^m e^(rt)] = e^(rt) sum(m!/(m-i)! d[i] t^(m-i)), where d[i] = 0 for i < s,
+         * so matching the coefficients of t^q gives a triangular system for the h_k */
+        let characteristic: Vec<Complex> = characteristic.iter().map(|&a| Complex::from(a)).collect();
+        let d = taylor(&characteristic, self.rate, n + s + 1);
+
+        let mut h = vec![Complex::ZERO; n + 1];
+
+        for q in (0..=n).rev() {
+            let known = (q + 1..=n).fold(Complex::ZERO, |acc, k| acc + h[k] * Complex::from(falling(k + s, q)) * d[k + s - q]);
+            h[q] = (self.coefficients[q] - known) / (Complex::from(falling(q + s, q)) * d[s]);
+        }
+
+        let mut coefficients = vec![Complex::ZERO; s];
+        coefficients.extend(h);
+
+        Family { rate: self.rate, coefficients }
+    }
+
+    /// the `k`th derivative of the (real) function at 0.
+    fn derivative_at_zero(&self, k: usize) -> f64 {
+        self.coefficients.iter().enumerate()
+            .fold(Complex::ZERO, |acc, (j, &c)| acc + c * derivative_at_zero(k, j, self.rate))
+            .re
+    }
+}
+
+/// the homogeneous solutions of an equation with these characteristic roots, as `(j, r, imaginary)`:
+/// `t^j e^(rt)` for real roots, its real and imaginary parts `t^j e^(at) cos(bt)` and `t^j e^(at) sin(bt)`
+/// for each conjugate pair `a ± bi`.
+pub(crate) fn fundamental_set(roots: &[(Complex, usize)]) -> Vec<(usize, Complex, bool)> {
+    roots.iter()
+        .filter(|(root, _)| root.im >= 0.0)
+        .flat_map(|&(root, multiplicity)| (0..multiplicity).flat_map(move |j| match tidy(root.im).is_zero() {
+            true => vec![(j, Complex::from(root.re), false)],
+            false => vec![(j, root, false), (j, root, true)],
+        }))
+        .collect()
+}
+
+/// one of the `fundamental_set` as a term in `t`.
+pub(crate) fn solution_in<'a>(t: Name, (j, rate, imaginary): (usize, Complex, bool), arena: &'a Bump) -> Term<'a> {
+    use Term::*;
+
+    let trig = match (tidy(rate.im), imaginary) {
+        (b, _) if b.is_zero() => None,
+        (b, false) => Some(Cos(time_scaled(t, b, arena))),
+        (b, true) => Some(Sin(time_scaled(t, b, arena))),
+    };
+
+    time_factor(t, j, tidy(rate.re), trig, arena)
+}
+
+/// the summands of a sum together with the factor they're scaled by.
+fn summands<'t, 'a>(term: &'t Term<'a>) -> Vec<(f64, &'t Term<'a>)> {
+    use Term::*;
+
+    let scaled = |k: f64, term| summands(term).into_iter().map(|(c, term)| (k * c, term)).collect();
+
+    match term {
+        Sum(t1, t2) => summands(t1).into_iter().chain(summands(t2)).collect(),
+        Scale { coefficient, term } if matches!(**term, Sum(..)) => scaled(coefficient.to_f64(), term),
+        Negation(term) if matches!(**term, Sum(..)) => scaled(-1.0, term),
+        _ => vec![(1.0, term)],
+    }
+}
+
+/// `d^k/dt^k (t^j e^(rt))` at 0, which is `k!/(k-j)! r^(k-j)`.
+fn derivative_at_zero(k: usize, j: usize, rate: Complex) -> Complex {
+    match k < j {
+        true => Complex::ZERO,
+        false => (0..k - j).fold(Complex::from(falling(k, k - j)), |power, _| power * rate),
+    }
+}
+
+/// `n!/m!`
+fn falling(n: usize, m: usize) -> f64 {
+    (m + 1..=n).map(|k| k as f64).product()
+}
+
+fn close(a: Complex, b: Complex) -> bool {
+    (a - b).abs() < 1e-6
+}
+
+/// adds `c*factor` to the terms, leaving it out if `c` is (numerically) zero.
+fn push<'a>(terms: &mut Vec<Term<'a>>, c: f64, factor: Term<'a>, arena: &'a Bump) {
+    use Term::*;
+
+    let c = tidy(c);
+
+    if c.is_zero() {
+        return;
+    }
+
+    terms.push(match factor {
+        Constant(k) => Constant(c * k),
+        factor if c.is_one() => factor,
+        factor => Scale { coefficient: c, term: ArenaTerm::new_in(factor, arena) },
+    });
+}
+
+/// solves `matrix x = rhs` by gaussian elimination with partial pivoting, `None` if the matrix is singular.
+fn gaussian_elimination(mut matrix: Vec<Vec<f64>>, mut rhs: Vec<f64>) -> Option<Vec<f64>> {
+    let n = rhs.len();
+
+    for column in 0..n {
+        let pivot = (column..n).max_by(|&i, &j| matrix[i][column].abs().total_cmp(&matrix[j][column].abs()))?;
+
+        if matrix[pivot][column].abs() < 1e-12 {
+            return None;
+        }
+
+        matrix.swap(column, pivot);
+        rhs.swap(column, pivot);
+
+        let (above, below) = matrix.split_at_mut(column + 1);
+        let pivot_row = &above[column];
+
+        for (offset, row) in below.iter_mut().enumerate() {
+            let factor = row[column] / pivot_row[column];
+
+            for (a, p) in row.iter_mut().zip(pivot_row).skip(column) {
+                *a -= factor * p;
+            }
+
+            rhs[column + 1 + offset] -= factor * rhs[column];
+        }
+    }
+
+    let mut x = vec![0.0; n];
+
+    for row in (0..n).rev() {
+        let known: f64 = (row + 1..n).map(|k| matrix[row][k] * x[k]).sum();
+        x[row] = (rhs[row] - known) / matrix[row][row];
+    }
+
+    Some(x)
+}
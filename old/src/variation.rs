@@ -0,0 +1,89 @@
+use crate::{Term, ArenaTerm, LadesError, Number, Equation, Rules};
+use crate::equation::{as_var, determinant};
+use crate::roots::evaluated_roots;
+use crate::undetermined::{fundamental_set, solution_in};
+use bumpalo::Bump;
+use std::sync::LazyLock;
+
+/// the standard rules and the trigonometric identities, which wronskians of sines and cosines need to collapse.
+static WRONSKIAN_RULES: LazyLock<Rules> = LazyLock::new(|| {
+    let mut rules = Rules::default();
+    rules.register_trigonometry();
+    rules
+});
+
+impl<'a> Equation<'a> {
+    /// a particular solution by variation of parameters, `sum(y_k integral(W_k g/(a_n W)))`, where the `y_k`
+    /// solve the homogeneous equation, `W` is their wronskian and `W_k` is `W` with column `k` replaced by
+    /// `(0, ..., 0, 1)`. any forcing `g` goes, the integrals that have no closed form stay `Integral`s.
+    pub fn particular_solution_in(&self, unknown: Term, wrt_time: Term, arena: &'a Bump) -> Result<ArenaTerm<'a>, LadesError> {
+        use Term::*;
+
+        let new_in = |term| ArenaTerm::new_in(term, arena);
+
+        let y = as_var(unknown)?;
+        let t = as_var(wrt_time)?;
+        let ode = self.linear_in(&[y], t, arena)?;
+
+        let order = ode.coefficients[0].iter().rposition(|a| !a.is_zero()).ok_or_else(|| LadesError::NoUnknown(y.to_string()))?;
+        let leading = ode.coefficients[0][order];
+
+        let Some(forcing) = ode.forcing.into_iter().map(new_in).reduce(|sum, term| new_in(Sum(sum, term))) else {
+            return Ok(new_in(Constant(Number::from(0))));
+        };
+
+        if order == 0 {
+            return Scale { coefficient: Number::from(1) / leading, term: forcing }.simplify_in(arena);
+        }
+
+        let basis: Vec<ArenaTerm> = fundamental_set(&evaluated_roots(&ode.coefficients[0][..=order], arena)?).into_iter()
+            .map(|solution| new_in(solution_in(t, solution, arena)))
+            .collect();
+
+        /* row i holds the ith derivatives of the basis */
+        let mut matrix = vec![basis.clone()];
+
+        for i in 1..order {
+            let row = matrix[i - 1].iter().map(|y| (**y).clone().differentiate_in(Var(t), arena)).collect::<Result<_, _>>()?;
+            matrix.push(row);
+        }
+
+        let wronskian = determinant(&matrix, arena).canonicalize_in(arena)?.simplify_with(&WRONSKIAN_RULES, &mut (), arena)?;
+        let denominator = new_in(Scale { coefficient: leading, term: wronskian });
+
+        let mut solution = None;
+
+        for (k, y) in basis.into_iter().enumerate() {
+            let replaced: Vec<Vec<ArenaTerm>> = matrix.iter().enumerate()
+                .map(|(i, row)| row.iter().enumerate().map(|(j, a)| match j == k {
+                    true => new_in(Constant(Number::from(if i == order - 1 { 1 } else { 0 }))),
+                    false => a.clone(),
+                }).collect())
+                .collect();
+
+            let integrand = Quotient(new_in(Product(determinant(&replaced, arena), forcing.clone())), denominator.clone())
+                .canonicalize_in(arena)?
+                .simplify_with(&WRONSKIAN_RULES, &mut (), arena)?;
+
+            let part = new_in(distributed(&y, &*integrand.integrate_in(Var(t), arena)?, arena));
+
+            solution = Some(match solution {
+                Some(solution) => new_in(Sum(solution, part)),
+                None => part,
+            });
+        }
+
+        solution.unwrap().canonicalize_in(arena)?.simplify_in(arena)
+    }
+}
+
+/// `y` times each summand of `u`, so canonical form gets to cancel `e^(at)` against `e^(-at)`.
+fn distributed<'a>(y: &ArenaTerm<'a>, u: &Term<'a>, arena: &'a Bump) -> Term<'a> {
+    use Term::*;
+
+    match u {
+        Sum(t1, t2) => Sum(ArenaTerm::new_in(distributed(y, t1, arena), arena), ArenaTerm::new_in(distributed(y, t2, arena), arena)),
+        Scale { coefficient, term } => Scale { coefficient: *coefficient, term: ArenaTerm::new_in(distributed(y, term, arena), arena) },
+        u => Product(y.clone(), ArenaTerm::new_in(u.clone(), arena)),
+    }
+}